@@ -0,0 +1,495 @@
+use chrono::{DateTime, FixedOffset};
+use hex_color::HexColor;
+
+use crate::payload::{
+    Action, Align, Animation, ButtonSpec, ChartSpec, ClockSpec, CountdownSpec, Direction, Easing,
+    Fit, Font, ImageFrame, ImageSource, ImageSpec, Keyframe, Orientation, Payload, ProgressSpec,
+    Scroll, Style, Target, TextSpec, Threshold,
+};
+
+impl Payload {
+    /// An empty page
+    pub fn new() -> Payload {
+        Payload::default()
+    }
+
+    /// Add a button
+    pub fn button(mut self, button: ButtonSpec) -> Payload {
+        self.buttons.push(button);
+        self
+    }
+
+    /// Add several buttons, for example from [`crate::layout::Grid::place`]
+    pub fn buttons(mut self, buttons: impl IntoIterator<Item = ButtonSpec>) -> Payload {
+        self.buttons.extend(buttons);
+        self
+    }
+
+    /// Add a text
+    pub fn text(mut self, text: TextSpec) -> Payload {
+        self.text.push(text);
+        self
+    }
+
+    /// Add a clock
+    pub fn clock(mut self, clock: ClockSpec) -> Payload {
+        self.clocks.push(clock);
+        self
+    }
+
+    /// Add a countdown
+    pub fn countdown(mut self, countdown: CountdownSpec) -> Payload {
+        self.countdowns.push(countdown);
+        self
+    }
+
+    /// Add an image
+    pub fn image(mut self, image: ImageSpec) -> Payload {
+        self.images.push(image);
+        self
+    }
+
+    /// Add a progress bar
+    pub fn progress(mut self, progress: ProgressSpec) -> Payload {
+        self.progress.push(progress);
+        self
+    }
+
+    /// Add a bar chart
+    pub fn chart(mut self, chart: ChartSpec) -> Payload {
+        self.charts.push(chart);
+        self
+    }
+
+    /// Have the client load the page again after the given number of seconds
+    pub fn refresh_after_secs(mut self, secs: u32) -> Payload {
+        self.refresh_after_secs = Some(secs);
+        self
+    }
+
+    /// Color for buttons without a press color of their own while they are pressed
+    pub fn default_press_color(mut self, color: HexColor) -> Payload {
+        self.default_press_color = Some(color);
+        self
+    }
+
+    /// Define a glyph for the given character, as rows of pixels where `#` is
+    /// lit
+    pub fn glyph(mut self, c: char, rows: impl IntoIterator<Item = impl Into<String>>) -> Payload {
+        self.glyphs
+            .insert(c, rows.into_iter().map(Into::into).collect());
+        self
+    }
+}
+
+impl ButtonSpec {
+    /// A plain button of one pad, that does nothing when pressed
+    pub fn new(x: u32, y: u32, color: HexColor) -> ButtonSpec {
+        ButtonSpec {
+            x,
+            y,
+            color,
+            style: Style::Plain,
+            width: None,
+            height: None,
+            press_color: None,
+            on_press: None,
+            animation: None,
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> ButtonSpec {
+        self.style = style;
+        self
+    }
+
+    /// Make the button span the given number of pads to the right
+    pub fn width(mut self, width: u8) -> ButtonSpec {
+        self.width = Some(width);
+        self
+    }
+
+    /// Make the button span the given number of pads downwards
+    pub fn height(mut self, height: u8) -> ButtonSpec {
+        self.height = Some(height);
+        self
+    }
+
+    pub fn press_color(mut self, color: HexColor) -> ButtonSpec {
+        self.press_color = Some(color);
+        self
+    }
+
+    pub fn on_press(mut self, action: Action) -> ButtonSpec {
+        self.on_press = Some(action);
+        self
+    }
+
+    pub fn animation(mut self, animation: Animation) -> ButtonSpec {
+        self.animation = Some(animation);
+        self
+    }
+}
+
+impl TextSpec {
+    /// A text that is as wide as the grid allows
+    pub fn new(x: u32, y: u32, text: impl Into<String>, color: HexColor) -> TextSpec {
+        TextSpec {
+            x,
+            y,
+            text: text.into(),
+            color,
+            width: None,
+            font: Font::Normal,
+            align: Align::Left,
+            scroll: Scroll::Bounce,
+            direction: Direction::Horizontal,
+            speed: None,
+            pause_ms: None,
+            height: None,
+        }
+    }
+
+    /// Limit the text to the given number of pads; longer texts scroll
+    pub fn width(mut self, width: u32) -> TextSpec {
+        self.width = Some(width);
+        self
+    }
+
+    pub fn font(mut self, font: Font) -> TextSpec {
+        self.font = font;
+        self
+    }
+
+    pub fn align(mut self, align: Align) -> TextSpec {
+        self.align = align;
+        self
+    }
+
+    pub fn scroll(mut self, scroll: Scroll) -> TextSpec {
+        self.scroll = scroll;
+        self
+    }
+
+    /// Wrap the text into lines in a box of the given number of pads high, and
+    /// move the lines upwards if they don't fit
+    pub fn vertical(mut self, height: u32) -> TextSpec {
+        self.direction = Direction::Vertical;
+        self.height = Some(height);
+        self
+    }
+
+    /// Move the text at the given number of pads per second
+    pub fn speed(mut self, speed: u32) -> TextSpec {
+        self.speed = Some(speed);
+        self
+    }
+
+    /// Stand still for the given number of milliseconds before moving
+    pub fn pause_ms(mut self, pause_ms: u32) -> TextSpec {
+        self.pause_ms = Some(pause_ms);
+        self
+    }
+}
+
+impl ClockSpec {
+    /// A clock that shows hours and minutes in local time
+    pub fn new(x: u32, y: u32, color: HexColor) -> ClockSpec {
+        ClockSpec {
+            x,
+            y,
+            format: "%H:%M".to_string(),
+            timezone: "local".to_string(),
+            color,
+            width: None,
+            font: Font::Normal,
+            align: Align::Left,
+            scroll: Scroll::Bounce,
+        }
+    }
+
+    /// Show the time in the given `strftime` format
+    pub fn format(mut self, format: impl Into<String>) -> ClockSpec {
+        self.format = format.into();
+        self
+    }
+
+    /// Show the time in `local` time, `UTC`, a timezone like `Europe/Amsterdam`,
+    /// or at a fixed offset like `+02:00`
+    pub fn timezone(mut self, timezone: impl Into<String>) -> ClockSpec {
+        self.timezone = timezone.into();
+        self
+    }
+
+    /// Limit the clock to the given number of pads
+    pub fn width(mut self, width: u32) -> ClockSpec {
+        self.width = Some(width);
+        self
+    }
+
+    pub fn font(mut self, font: Font) -> ClockSpec {
+        self.font = font;
+        self
+    }
+
+    pub fn align(mut self, align: Align) -> ClockSpec {
+        self.align = align;
+        self
+    }
+
+    pub fn scroll(mut self, scroll: Scroll) -> ClockSpec {
+        self.scroll = scroll;
+        self
+    }
+}
+
+impl CountdownSpec {
+    /// A countdown to the given moment, in minutes and seconds
+    pub fn new(x: u32, y: u32, to: DateTime<FixedOffset>, color: HexColor) -> CountdownSpec {
+        CountdownSpec {
+            x,
+            y,
+            to,
+            format: None,
+            finished_text: None,
+            color,
+            width: None,
+            font: Font::Normal,
+            align: Align::Left,
+            scroll: Scroll::Bounce,
+        }
+    }
+
+    /// Show the time that is left in the given format, where `%H`, `%M` and
+    /// `%S` are the hours, minutes and seconds
+    pub fn format(mut self, format: impl Into<String>) -> CountdownSpec {
+        self.format = Some(format.into());
+        self
+    }
+
+    /// Show the given text once the countdown has reached zero
+    pub fn finished_text(mut self, text: impl Into<String>) -> CountdownSpec {
+        self.finished_text = Some(text.into());
+        self
+    }
+
+    /// Limit the countdown to the given number of pads
+    pub fn width(mut self, width: u32) -> CountdownSpec {
+        self.width = Some(width);
+        self
+    }
+
+    pub fn font(mut self, font: Font) -> CountdownSpec {
+        self.font = font;
+        self
+    }
+
+    pub fn align(mut self, align: Align) -> CountdownSpec {
+        self.align = align;
+        self
+    }
+
+    pub fn scroll(mut self, scroll: Scroll) -> CountdownSpec {
+        self.scroll = scroll;
+        self
+    }
+}
+
+impl ImageSpec {
+    /// An image from the given rows of pixels; `None` pixels are transparent
+    pub fn pixels(x: u32, y: u32, pixels: Vec<Vec<Option<HexColor>>>) -> ImageSpec {
+        ImageSpec::new(x, y, ImageSource::Pixels { pixels })
+    }
+
+    /// An animation of the given frames
+    pub fn frames(x: u32, y: u32, frames: Vec<ImageFrame>) -> ImageSpec {
+        ImageSpec::new(x, y, ImageSource::Frames { frames })
+    }
+
+    /// A PNG or GIF image that the client loads from the given URL, relative to
+    /// the page
+    pub fn src(x: u32, y: u32, src: impl Into<String>) -> ImageSpec {
+        ImageSpec::new(x, y, ImageSource::Src { src: src.into() })
+    }
+
+    fn new(x: u32, y: u32, source: ImageSource) -> ImageSpec {
+        ImageSpec {
+            x,
+            y,
+            width: None,
+            height: None,
+            fit: Fit::Scale,
+            source,
+        }
+    }
+
+    /// Scale or crop the image to the given number of pads
+    pub fn size(mut self, width: u32, height: u32) -> ImageSpec {
+        self.width = Some(width);
+        self.height = Some(height);
+        self
+    }
+
+    pub fn fit(mut self, fit: Fit) -> ImageSpec {
+        self.fit = fit;
+        self
+    }
+}
+
+impl ProgressSpec {
+    /// An empty horizontal bar of the given number of pads, that is full at 100
+    pub fn new(x: u32, y: u32, length: u32, color: HexColor) -> ProgressSpec {
+        ProgressSpec {
+            x,
+            y,
+            length,
+            orientation: Orientation::Horizontal,
+            value: 0.0,
+            max: 100.0,
+            colors: vec![color],
+            background: None,
+        }
+    }
+
+    pub fn value(mut self, value: f64) -> ProgressSpec {
+        self.value = value;
+        self
+    }
+
+    /// Make the bar full at the given value instead of at 100
+    pub fn max(mut self, max: f64) -> ProgressSpec {
+        self.max = max;
+        self
+    }
+
+    /// Make the bar fill up from bottom to top
+    pub fn vertical(mut self) -> ProgressSpec {
+        self.orientation = Orientation::Vertical;
+        self
+    }
+
+    /// Blend the bar between the given colors, from its empty end to its full
+    /// end
+    pub fn colors(mut self, colors: impl IntoIterator<Item = HexColor>) -> ProgressSpec {
+        self.colors = colors.into_iter().collect();
+        self
+    }
+
+    pub fn background(mut self, color: HexColor) -> ProgressSpec {
+        self.background = Some(color);
+        self
+    }
+}
+
+impl ChartSpec {
+    /// A chart of the given height, with a column for each value, scaled so
+    /// the highest value reaches the top
+    pub fn new(
+        x: u32,
+        y: u32,
+        height: u32,
+        values: impl IntoIterator<Item = f64>,
+        color: HexColor,
+    ) -> ChartSpec {
+        ChartSpec {
+            x,
+            y,
+            width: None,
+            height,
+            values: values.into_iter().collect(),
+            min: None,
+            max: None,
+            color,
+            thresholds: vec![],
+            background: None,
+        }
+    }
+
+    /// Show only the last values that fit in the given number of columns
+    pub fn width(mut self, width: u32) -> ChartSpec {
+        self.width = Some(width);
+        self
+    }
+
+    /// Scale the chart from `min` at the bottom to `max` at the top, instead
+    /// of to the values
+    pub fn range(mut self, min: f64, max: f64) -> ChartSpec {
+        self.min = Some(min);
+        self.max = Some(max);
+        self
+    }
+
+    /// Color the columns with at least the given value
+    pub fn threshold(mut self, value: f64, color: HexColor) -> ChartSpec {
+        self.thresholds.push(Threshold { value, color });
+        self
+    }
+
+    pub fn background(mut self, color: HexColor) -> ChartSpec {
+        self.background = Some(color);
+        self
+    }
+}
+
+impl Animation {
+    /// A linear animation through the given keyframes that loops
+    pub fn new(keyframes: Vec<Keyframe>) -> Animation {
+        Animation {
+            keyframes,
+            easing: Easing::Linear,
+            looping: true,
+            offset_ms: 0,
+        }
+    }
+
+    /// Go through the given colors, taking the same time for each
+    pub fn cycle(colors: &[HexColor], duration_ms: u32) -> Animation {
+        Animation::new(
+            colors
+                .iter()
+                .map(|&color| Keyframe::new(color, duration_ms))
+                .collect(),
+        )
+    }
+
+    pub fn easing(mut self, easing: Easing) -> Animation {
+        self.easing = easing;
+        self
+    }
+
+    /// Stop at the last keyframe instead of starting over
+    pub fn once(mut self) -> Animation {
+        self.looping = false;
+        self
+    }
+
+    pub fn offset_ms(mut self, offset_ms: u32) -> Animation {
+        self.offset_ms = offset_ms;
+        self
+    }
+}
+
+impl Keyframe {
+    pub fn new(color: HexColor, duration_ms: u32) -> Keyframe {
+        Keyframe { color, duration_ms }
+    }
+}
+
+impl Action {
+    /// Navigate to the given URL, relative to the current page
+    pub fn navigate(href: impl Into<String>) -> Action {
+        Action::Navigate(Target::Href { href: href.into() })
+    }
+
+    /// Navigate to a page that is given inline
+    pub fn navigate_to_page(page: Payload) -> Action {
+        Action::Navigate(Target::Page {
+            page: Box::new(page),
+        })
+    }
+
+    /// Open a browser at the given URL
+    pub fn open(href: impl Into<String>) -> Action {
+        Action::Browser { href: href.into() }
+    }
+}
@@ -0,0 +1,147 @@
+use std::fmt;
+
+use http::{HeaderMap, HeaderValue};
+
+use crate::layout::{GRID_HEIGHT, GRID_WIDTH};
+
+/// Header with the version of the restpad client, like `0.1.0`
+pub static VERSION_HEADER: &str = "restpad-version";
+
+/// Header with the size of the grid that pages can use, like `8x8`
+pub static GRID_HEADER: &str = "restpad-grid";
+
+/// What the client that requests a page can show
+///
+/// restpad sends these as headers with every HTTP request, so servers can
+/// adapt their pages to the device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Version of the restpad client
+    pub version: String,
+
+    /// Number of columns of pads that pages can use
+    pub grid_width: u32,
+
+    /// Number of rows of pads that are visible at once
+    pub grid_height: u32,
+}
+
+impl Capabilities {
+    /// The capabilities of a client of the given version with the standard grid
+    pub fn new(version: impl Into<String>) -> Capabilities {
+        Capabilities {
+            version: version.into(),
+            grid_width: GRID_WIDTH,
+            grid_height: GRID_HEIGHT,
+        }
+    }
+
+    /// Read the capabilities from the headers of a request
+    pub fn from_headers(headers: &HeaderMap) -> Result<Capabilities, CapabilitiesError> {
+        let header = |name: &'static str| {
+            let value = headers.get(name).ok_or(CapabilitiesError::Missing(name))?;
+            value
+                .to_str()
+                .map_err(|_| CapabilitiesError::Invalid(name, format!("{:?}", value)))
+        };
+
+        let grid = header(GRID_HEADER)?;
+        let invalid_grid = || CapabilitiesError::Invalid(GRID_HEADER, grid.to_string());
+        let (width, height) = grid.split_once('x').ok_or_else(invalid_grid)?;
+        Ok(Capabilities {
+            version: header(VERSION_HEADER)?.to_string(),
+            grid_width: width.trim().parse().map_err(|_| invalid_grid())?,
+            grid_height: height.trim().parse().map_err(|_| invalid_grid())?,
+        })
+    }
+
+    /// The headers that announce these capabilities
+    pub fn to_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Ok(version) = HeaderValue::from_str(&self.version) {
+            headers.insert(VERSION_HEADER, version);
+        }
+        headers.insert(
+            GRID_HEADER,
+            HeaderValue::from_str(&format!("{}x{}", self.grid_width, self.grid_height))
+                .expect("numbers are valid header values"),
+        );
+        headers
+    }
+}
+
+/// Capabilities that could not be read from a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CapabilitiesError {
+    /// The header with the given name is missing, so the request probably
+    /// doesn't come from restpad
+    Missing(&'static str),
+
+    /// The header with the given name has a value that can't be parsed
+    Invalid(&'static str, String),
+}
+
+impl fmt::Display for CapabilitiesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CapabilitiesError::Missing(name) => write!(f, "Missing {} header", name),
+            CapabilitiesError::Invalid(name, value) => {
+                write!(f, "Invalid {} header: {}", name, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CapabilitiesError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headers_round_trip() {
+        let capabilities = Capabilities {
+            version: "1.2.3".to_string(),
+            grid_width: 8,
+            grid_height: 16,
+        };
+        let headers = capabilities.to_headers();
+        assert_eq!(headers[VERSION_HEADER], "1.2.3");
+        assert_eq!(headers[GRID_HEADER], "8x16");
+        assert_eq!(Capabilities::from_headers(&headers), Ok(capabilities));
+    }
+
+    #[test]
+    fn missing_header() {
+        let mut headers = Capabilities::new("0.1.0").to_headers();
+        headers.remove(VERSION_HEADER);
+        assert_eq!(
+            Capabilities::from_headers(&headers),
+            Err(CapabilitiesError::Missing(VERSION_HEADER))
+        );
+        assert_eq!(
+            Capabilities::from_headers(&HeaderMap::new()),
+            Err(CapabilitiesError::Missing(GRID_HEADER))
+        );
+    }
+
+    #[test]
+    fn invalid_grid() {
+        for grid in ["8", "8x", "x8", "eight by eight", "-1x8"] {
+            let mut headers = Capabilities::new("0.1.0").to_headers();
+            headers.insert(GRID_HEADER, HeaderValue::from_static(grid));
+            assert_eq!(
+                Capabilities::from_headers(&headers),
+                Err(CapabilitiesError::Invalid(GRID_HEADER, grid.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn grid_with_spaces() {
+        let mut headers = Capabilities::new("0.1.0").to_headers();
+        headers.insert(GRID_HEADER, HeaderValue::from_static("8 x 8"));
+        let capabilities = Capabilities::from_headers(&headers).unwrap();
+        assert_eq!((capabilities.grid_width, capabilities.grid_height), (8, 8));
+    }
+}
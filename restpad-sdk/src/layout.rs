@@ -0,0 +1,89 @@
+use crate::payload::{ButtonSpec, Font};
+
+/// Number of columns of pads that pages can use
+///
+/// The column to the right of these is the client's scroll bar.
+pub static GRID_WIDTH: u32 = 8;
+
+/// Number of rows of pads that are visible at once
+///
+/// Pages can be taller than this; the client scrolls them.
+pub static GRID_HEIGHT: u32 = 8;
+
+/// Places buttons next to each other, left to right and top to bottom
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Grid {
+    /// Column of the first button
+    pub x: u32,
+
+    /// Row of the first button
+    pub y: u32,
+
+    /// Number of buttons on each row
+    pub columns: u32,
+}
+
+impl Grid {
+    /// A grid that starts in the top left corner, with the given number of
+    /// buttons on each row
+    pub fn new(columns: u32) -> Grid {
+        Grid {
+            x: 0,
+            y: 0,
+            columns: columns.max(1),
+        }
+    }
+
+    /// A grid that fills the full width of the pad
+    pub fn full_width() -> Grid {
+        Grid::new(GRID_WIDTH)
+    }
+
+    /// Start the grid at the given pad instead
+    pub fn at(self, x: u32, y: u32) -> Grid {
+        Grid { x, y, ..self }
+    }
+
+    /// The pad of the button with the given index
+    pub fn position(&self, index: usize) -> (u32, u32) {
+        let index = index as u32;
+        (self.x + index % self.columns, self.y + index / self.columns)
+    }
+
+    /// Number of rows that the given number of buttons take up
+    pub fn rows(&self, count: usize) -> u32 {
+        (count as u32).div_ceil(self.columns)
+    }
+
+    /// Move the given buttons into the grid, in order
+    pub fn place(&self, buttons: impl IntoIterator<Item = ButtonSpec>) -> Vec<ButtonSpec> {
+        buttons
+            .into_iter()
+            .enumerate()
+            .map(|(i, button)| {
+                let (x, y) = self.position(i);
+                ButtonSpec { x, y, ..button }
+            })
+            .collect()
+    }
+}
+
+impl Font {
+    /// Number of pads each character takes up horizontally, including the
+    /// space after it
+    pub fn char_width(self) -> u32 {
+        match self {
+            Font::Small | Font::Normal => 4,
+            Font::Large | Font::Bold => 5,
+        }
+    }
+
+    /// Height of a line of text, in pads
+    pub fn height(self) -> u32 {
+        match self {
+            Font::Small => 5,
+            Font::Normal | Font::Bold => 6,
+            Font::Large => 7,
+        }
+    }
+}
@@ -0,0 +1,27 @@
+//! Build pages for restpad in Rust
+//!
+//! The types in [`payload`] are the page format itself, shared with the
+//! restpad client, so pages built with them always match what the client
+//! expects. They come with chainable constructors:
+//!
+//! ```
+//! use hex_color::HexColor;
+//! use restpad_sdk::payload::{Action, ButtonSpec, Payload, TextSpec};
+//!
+//! let page = Payload::new()
+//!     .button(ButtonSpec::new(0, 0, HexColor::RED).on_press(Action::navigate("/lights")))
+//!     .text(TextSpec::new(0, 2, "Hello", HexColor::WHITE))
+//!     .refresh_after_secs(30);
+//! ```
+//!
+//! [`layout`] helps with placing buttons on the grid, and [`capabilities`]
+//! reads what the client that requested a page can show. With the `axum`
+//! feature, pages can be returned from axum handlers directly, and
+//! [`capabilities::Capabilities`] can be used as an extractor.
+
+mod builder;
+pub mod capabilities;
+pub mod layout;
+pub mod payload;
+#[cfg(feature = "axum")]
+mod server;
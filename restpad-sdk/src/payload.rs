@@ -0,0 +1,567 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, FixedOffset};
+use hex_color::HexColor;
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Schema, SchemaObject, StringValidation},
+    JsonSchema,
+};
+use serde::{Deserialize, Serialize};
+
+/// The payload of a page load
+#[derive(Clone, Serialize, Deserialize, Debug, Default, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Payload {
+    /// A list of button bindings
+    #[serde(default)]
+    pub buttons: Vec<ButtonSpec>,
+
+    /// A list of texts
+    #[serde(default)]
+    pub text: Vec<TextSpec>,
+
+    /// A list of clocks, drawn like texts
+    #[serde(default)]
+    pub clocks: Vec<ClockSpec>,
+
+    /// A list of countdowns, drawn like texts
+    #[serde(default)]
+    pub countdowns: Vec<CountdownSpec>,
+
+    /// A list of images, drawn below the buttons and texts
+    #[serde(default)]
+    pub images: Vec<ImageSpec>,
+
+    /// A list of progress bars, drawn above the images and below the buttons
+    /// and texts
+    #[serde(default)]
+    pub progress: Vec<ProgressSpec>,
+
+    /// A list of bar charts, drawn above the images and below the buttons and
+    /// texts
+    #[serde(default)]
+    pub charts: Vec<ChartSpec>,
+
+    /// The amount of seconds after which the page should automatically refresh
+    pub refresh_after_secs: Option<u32>,
+
+    /// For any of the buttons in this page, if no press color is set this color will be used
+    #[schemars(with = "Option<Color>")]
+    pub default_press_color: Option<HexColor>,
+
+    /// Glyphs for characters that the fonts don't have, like icons
+    ///
+    /// Each glyph is a list of rows of pixels from top to bottom, where `#` is
+    /// a lit pixel and anything else is dark. Texts show the glyph wherever they
+    /// contain its character.
+    #[serde(default)]
+    pub glyphs: BTreeMap<char, Vec<String>>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ButtonSpec {
+    /// X coordinate of this button
+    pub x: u32,
+
+    /// Y coordinate of this button
+    pub y: u32,
+
+    /// The color of this button
+    #[schemars(with = "Color")]
+    pub color: HexColor,
+
+    /// The style of this button
+    #[serde(default)]
+    pub style: Style,
+
+    /// The width of the button
+    pub width: Option<u8>,
+
+    /// The height of the button
+    pub height: Option<u8>,
+
+    /// The color this button should have if it is being pressed
+    #[schemars(with = "Option<Color>")]
+    pub press_color: Option<HexColor>,
+
+    /// Action to perform when this button is pressed
+    pub on_press: Option<Action>,
+
+    /// Colors the button goes through over time, instead of its color and style
+    pub animation: Option<Animation>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Animation {
+    /// The colors to go through, in order
+    pub keyframes: Vec<Keyframe>,
+
+    /// How the color changes from one keyframe to the next
+    #[serde(default)]
+    pub easing: Easing,
+
+    /// Whether to start over after the last keyframe, fading back into the
+    /// first one. Otherwise the button keeps the color of the last keyframe.
+    #[serde(default = "default_loop", rename = "loop")]
+    pub looping: bool,
+
+    /// How far into the animation the button starts, in milliseconds, so that
+    /// buttons with the same keyframes can run one after another
+    #[serde(default)]
+    pub offset_ms: u32,
+}
+
+fn default_loop() -> bool {
+    true
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Keyframe {
+    #[schemars(with = "Color")]
+    pub color: HexColor,
+
+    /// How long it takes to get from this color to the next one
+    pub duration_ms: u32,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum Easing {
+    /// Fade evenly from one color to the next
+    #[default]
+    Linear,
+
+    /// Fade slowly at the start and end of every keyframe
+    EaseInOut,
+
+    /// Keep the color of every keyframe until the next one starts
+    Step,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TextSpec {
+    /// X coordinate of this button
+    pub x: u32,
+
+    /// Y coordinate of this button
+    pub y: u32,
+
+    /// The text to print
+    pub text: String,
+
+    /// The color of this button
+    #[schemars(with = "Color")]
+    pub color: HexColor,
+
+    /// Maximum width of the text
+    pub width: Option<u32>,
+
+    /// The font the text is drawn in
+    #[serde(default)]
+    pub font: Font,
+
+    /// Where the text is placed within its width, if it fits
+    #[serde(default)]
+    pub align: Align,
+
+    /// How the text moves if it doesn't fit
+    #[serde(default)]
+    pub scroll: Scroll,
+
+    /// Which way the text moves if it doesn't fit
+    #[serde(default)]
+    pub direction: Direction,
+
+    /// How fast the text moves, in pads per second; defaults to 10
+    pub speed: Option<u32>,
+
+    /// How long the text stands still before it moves, in milliseconds;
+    /// defaults to 1000
+    pub pause_ms: Option<u32>,
+
+    /// Height of vertically moving text, in pads; defaults to one line
+    pub height: Option<u32>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum Font {
+    /// 3x5 pixels per character, for compact text
+    Small,
+
+    /// 4x6 pixels per character
+    #[default]
+    Normal,
+
+    /// 5x7 pixels per character
+    Large,
+
+    /// The normal font in bold, one pixel wider per character
+    Bold,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockSpec {
+    /// X coordinate of the clock
+    pub x: u32,
+
+    /// Y coordinate of the clock
+    pub y: u32,
+
+    /// How the time is shown, as a `strftime` format; defaults to `%H:%M`
+    #[serde(default = "default_clock_format")]
+    pub format: String,
+
+    /// `local`, `UTC`, a timezone like `Europe/Amsterdam`, or a fixed offset
+    /// from UTC like `+02:00`; defaults to `local`
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+
+    /// The color of the clock
+    #[schemars(with = "Color")]
+    pub color: HexColor,
+
+    /// Maximum width of the clock
+    pub width: Option<u32>,
+
+    /// The font the clock is drawn in
+    #[serde(default)]
+    pub font: Font,
+
+    /// Where the clock is placed within its width, if it fits
+    #[serde(default)]
+    pub align: Align,
+
+    /// How the clock moves if it doesn't fit
+    #[serde(default)]
+    pub scroll: Scroll,
+}
+
+fn default_clock_format() -> String {
+    "%H:%M".to_string()
+}
+
+fn default_timezone() -> String {
+    "local".to_string()
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CountdownSpec {
+    /// X coordinate of the countdown
+    pub x: u32,
+
+    /// Y coordinate of the countdown
+    pub y: u32,
+
+    /// When the countdown reaches zero, like `2024-05-01T09:30:00+02:00`
+    #[schemars(with = "String")]
+    pub to: DateTime<FixedOffset>,
+
+    /// How the time that is left is shown, where `%H`, `%M` and `%S` are the
+    /// hours, minutes and seconds; defaults to `%M:%S`, with the hours in front
+    /// if there are any
+    ///
+    /// `%M` counts all minutes if there is no `%H`.
+    pub format: Option<String>,
+
+    /// What is shown once the countdown has reached zero; defaults to the
+    /// format with zeros
+    pub finished_text: Option<String>,
+
+    /// The color of the countdown
+    #[schemars(with = "Color")]
+    pub color: HexColor,
+
+    /// Maximum width of the countdown
+    pub width: Option<u32>,
+
+    /// The font the countdown is drawn in
+    #[serde(default)]
+    pub font: Font,
+
+    /// Where the countdown is placed within its width, if it fits
+    #[serde(default)]
+    pub align: Align,
+
+    /// How the countdown moves if it doesn't fit
+    #[serde(default)]
+    pub scroll: Scroll,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum Scroll {
+    /// The text moves to its end, then back to its start
+    #[default]
+    Bounce,
+
+    /// The text keeps moving, and starts over once it has moved out of view
+    Loop,
+
+    /// The text stands still, and whatever doesn't fit is cut off
+    None,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum Direction {
+    /// The text is a single line that moves sideways
+    #[default]
+    Horizontal,
+
+    /// The text is wrapped to its width, and the lines move upwards
+    Vertical,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageSpec {
+    /// X coordinate of the top left corner of the image
+    pub x: u32,
+
+    /// Y coordinate of the top left corner of the image
+    pub y: u32,
+
+    /// Width of the image on the grid, in pads
+    ///
+    /// Defaults to the width of the image, up to the right edge of the grid.
+    pub width: Option<u32>,
+
+    /// Height of the image on the grid, in pads
+    ///
+    /// Defaults to the height of the image, up to the height of the grid.
+    pub height: Option<u32>,
+
+    /// How the image is made to fit its width and height
+    #[serde(default)]
+    pub fit: Fit,
+
+    /// The pixels of the image, or where to load them from
+    #[serde(flatten)]
+    pub source: ImageSource,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressSpec {
+    /// X coordinate of the left end of the bar
+    pub x: u32,
+
+    /// Y coordinate of the top end of the bar
+    pub y: u32,
+
+    /// Number of pads the bar spans
+    pub length: u32,
+
+    /// Which way the bar fills up
+    #[serde(default)]
+    pub orientation: Orientation,
+
+    /// How far the bar is filled, from 0 to `max`
+    pub value: f64,
+
+    /// The value at which the bar is full; defaults to 100
+    #[serde(default = "default_max")]
+    pub max: f64,
+
+    /// Colors of the bar from its empty end to its full end, blended in between
+    #[schemars(with = "Vec<Color>")]
+    pub colors: Vec<HexColor>,
+
+    /// Color of the part of the bar that isn't filled; defaults to dark
+    #[schemars(with = "Option<Color>")]
+    pub background: Option<HexColor>,
+}
+
+fn default_max() -> f64 {
+    100.0
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChartSpec {
+    /// X coordinate of the leftmost column
+    pub x: u32,
+
+    /// Y coordinate of the top of the chart
+    pub y: u32,
+
+    /// Number of columns; defaults to one for each value, up to the right edge
+    /// of the grid
+    ///
+    /// If there are more values than columns, the last ones are shown.
+    pub width: Option<u32>,
+
+    /// Number of pads the highest column reaches
+    pub height: u32,
+
+    /// The values of the columns, from left to right
+    pub values: Vec<f64>,
+
+    /// The value at the bottom of the chart; defaults to 0, or to the lowest
+    /// value if that is below 0
+    pub min: Option<f64>,
+
+    /// The value at the top of the chart; defaults to the highest value
+    pub max: Option<f64>,
+
+    /// Color of the columns
+    #[schemars(with = "Color")]
+    pub color: HexColor,
+
+    /// Other colors for columns with high values
+    #[serde(default)]
+    pub thresholds: Vec<Threshold>,
+
+    /// Color of the pads above the columns; defaults to dark
+    #[schemars(with = "Option<Color>")]
+    pub background: Option<HexColor>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Threshold {
+    /// Columns with at least this value get the color, unless they reach a
+    /// higher threshold
+    pub value: f64,
+
+    #[schemars(with = "Color")]
+    pub color: HexColor,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum Orientation {
+    /// The bar fills up from left to right
+    #[default]
+    Horizontal,
+
+    /// The bar fills up from bottom to top
+    Vertical,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(untagged)]
+pub enum ImageSource {
+    /// Rows of pixels, from top to bottom
+    ///
+    /// `null` pixels are transparent.
+    Pixels {
+        #[schemars(with = "Vec<Vec<Option<Color>>>")]
+        pixels: Vec<Vec<Option<HexColor>>>,
+    },
+
+    /// An animation
+    Frames { frames: Vec<ImageFrame> },
+
+    /// A PNG or GIF image at the given URL, relative to the page
+    ///
+    /// All frames of an animated GIF are shown.
+    Src { src: String },
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageFrame {
+    /// Rows of pixels, from top to bottom
+    ///
+    /// `null` pixels are transparent.
+    #[schemars(with = "Vec<Vec<Option<Color>>>")]
+    pub pixels: Vec<Vec<Option<HexColor>>>,
+
+    /// How long the frame is shown, in milliseconds
+    #[serde(default = "default_delay_ms")]
+    pub delay_ms: u32,
+}
+
+fn default_delay_ms() -> u32 {
+    100
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum Fit {
+    /// The image is scaled to the width and height
+    #[default]
+    Scale,
+
+    /// The image keeps its size, and whatever doesn't fit is cut off at the
+    /// right and bottom
+    Crop,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum Style {
+    /// The button just lights up
+    #[default]
+    Plain,
+
+    /// The button pulses the given color
+    Pulse,
+
+    /// The button flashes between its primary color and the given color
+    ///
+    /// A missing color is interpreted as "black".
+    Flash(#[schemars(with = "Option<Color>")] Option<HexColor>),
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum Action {
+    /// Navigate to the given URL, or to a page given inline
+    #[serde(rename = "navigate")]
+    Navigate(Target),
+    /// Open a browser at the given URL
+    #[serde(rename = "open")]
+    Browser { href: String },
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(untagged)]
+pub enum Target {
+    /// The page at the given URL
+    Href { href: String },
+
+    /// A page that is given inline, so it doesn't need to be loaded
+    Page { page: Box<Payload> },
+}
+
+/// Stands in for `HexColor` in the JSON Schema
+///
+/// Colors are written as hex strings, like `#FF00FF`.
+struct Color;
+
+impl JsonSchema for Color {
+    fn schema_name() -> String {
+        "Color".to_string()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            string: Some(Box::new(StringValidation {
+                pattern: Some("^#([0-9a-fA-F]{3,4}|[0-9a-fA-F]{6}|[0-9a-fA-F]{8})$".to_string()),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
@@ -0,0 +1,36 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::{
+    capabilities::{Capabilities, CapabilitiesError},
+    payload::Payload,
+};
+
+/// Pages are served as JSON
+impl IntoResponse for Payload {
+    fn into_response(self) -> Response {
+        Json(self).into_response()
+    }
+}
+
+/// Requests that don't come from restpad are rejected with 400 Bad Request;
+/// use `Option<Capabilities>` to accept those too
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Capabilities {
+    type Rejection = CapabilitiesError;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        Capabilities::from_headers(&parts.headers)
+    }
+}
+
+impl IntoResponse for CapabilitiesError {
+    fn into_response(self) -> Response {
+        (StatusCode::BAD_REQUEST, self.to_string()).into_response()
+    }
+}
@@ -2,7 +2,7 @@ use super::RgbColor;
 
 /// Converts the given RGB color to the closest palette color from a 128 color palette
 ///
-/// Stolen from https://www.reddit.com/r/Novation/comments/vgitm0/reverse_engineering_launchpad_pro_color_to/.
+/// Stolen from <https://www.reddit.com/r/Novation/comments/vgitm0/reverse_engineering_launchpad_pro_color_to/>.
 ///
 /// This palette applies for the Mk2, Mk3 and Mk3 Mini.
 pub fn rgb_to_palette(color: RgbColor) -> u8 {
@@ -13,6 +13,28 @@ pub fn rgb_to_palette(color: RgbColor) -> u8 {
     COLORS[i]
 }
 
+/// Converts a palette color back to an approximate RGB color
+///
+/// This averages all RGB colors that `rgb_to_palette` maps to the given palette
+/// color. Palette colors that nothing maps to come out black.
+pub fn palette_to_rgb(palette: u8) -> RgbColor {
+    let (mut r, mut g, mut b, mut n) = (0u32, 0u32, 0u32, 0u32);
+    for (i, _) in COLORS.iter().enumerate().filter(|(_, &c)| c == palette) {
+        r += (i as u32 >> 6) * 255 / 7;
+        g += (i as u32 >> 3 & 7) * 255 / 7;
+        b += (i as u32 & 7) * 255 / 7;
+        n += 1;
+    }
+    match n {
+        0 => RgbColor { r: 0, g: 0, b: 0 },
+        n => RgbColor {
+            r: (r / n) as u8,
+            g: (g / n) as u8,
+            b: (b / n) as u8,
+        },
+    }
+}
+
 static COLORS: [u8; 512] = [
     0, 47, 51, 46, 46, 69, 69, 45, 23, 39, 51, 42, 50, 69, 69, 45, 64, 123, 102, 38, 66, 69, 69,
     79, 22, 30, 34, 68, 66, 66, 41, 41, 76, 76, 34, 66, 66, 66, 41, 41, 76, 122, 122, 122, 77, 33,
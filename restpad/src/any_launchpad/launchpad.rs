@@ -50,6 +50,7 @@ impl PaletteColor {
     pub const DARK_GRAY: PaletteColor = Self(1);
     pub const WHITE: PaletteColor = Self(3);
     pub const RED: PaletteColor = Self(5);
+    pub const GREEN: PaletteColor = Self(21);
     pub const YELLOW: PaletteColor = Self(13);
 }
 
@@ -0,0 +1,365 @@
+use std::{collections::HashSet, pin::Pin, time::Duration};
+
+use anyhow::bail;
+use chrono::Utc;
+use disk_persist::DiskPersist;
+use tokio::{
+    select,
+    time::{sleep, Sleep},
+};
+
+use crate::{
+    any_launchpad::{Button, InputMessage, Launchpad},
+    bookmarks::{bookmarks_page, BOOKMARKS_PAGE},
+    cookies::CookieJar,
+    credentials::Credentials,
+    navigator::Navigator,
+    payload::{Action, Target},
+    preferences::{Bookmarks, Preferences, Session},
+    render::PadState,
+    scheduler::FrameScheduler,
+    GRID_HEIGHT, GRID_WIDTH,
+};
+
+/// Shows pages on a Launchpad and handles its button presses
+pub struct RestPad {
+    prefs: Preferences,
+    bookmarks: Bookmarks,
+    navigator: Navigator,
+    lp: Box<dyn Launchpad>,
+    pressed_buttons: HashSet<Button>,
+    frames: FrameScheduler,
+    refresh_timer: Option<Pin<Box<Sleep>>>,
+    y_scroll: u32,
+    /// Scroll position to apply once the page being loaded has arrived
+    restore_y_scroll: Option<u32>,
+    /// Whether the SESSION button was used in a chord while it was held down
+    session_chorded: bool,
+    /// Whether the last page load failed
+    load_failed: bool,
+}
+
+impl RestPad {
+    /// Set up a RestPad on the given device, with the saved preferences
+    pub fn new(lp: Box<dyn Launchpad>, credentials: Credentials) -> anyhow::Result<Self> {
+        let prefs = DiskPersist::init("restpad")?.read()?.unwrap_or_default();
+        // Losing the bookmarks is no reason to refuse to start
+        let bookmarks: Bookmarks = print_error(DiskPersist::init("restpad-bookmarks")?.read())
+            .flatten()
+            .unwrap_or_default();
+
+        let mut navigator = Navigator::new(credentials, CookieJar::load()?)?;
+        navigator.set_builtin_page(BOOKMARKS_PAGE, bookmarks_page(&bookmarks.urls));
+
+        Ok(RestPad {
+            prefs,
+            bookmarks,
+            navigator,
+            lp,
+            pressed_buttons: Default::default(),
+            frames: FrameScheduler::default(),
+            refresh_timer: None,
+            y_scroll: 0,
+            restore_y_scroll: None,
+            session_chorded: false,
+            load_failed: false,
+        })
+    }
+
+    fn save_settings(&self) -> anyhow::Result<()> {
+        DiskPersist::init("restpad")?.write(&self.prefs)?;
+        Ok(())
+    }
+
+    fn save_bookmarks(&self) -> anyhow::Result<()> {
+        DiskPersist::init("restpad-bookmarks")?.write(&self.bookmarks)?;
+        Ok(())
+    }
+
+    /// Save the current page and scroll position, so we can resume there after a restart
+    fn save_session(&self) -> anyhow::Result<()> {
+        // Don't overwrite a good session with a page that failed to load
+        if self.navigator.current().is_none() {
+            return Ok(());
+        }
+        let session = Session {
+            navigation: Some(self.navigator.state()),
+            y_scroll: self.y_scroll,
+        };
+        DiskPersist::init("restpad-session")?.write(&session)?;
+        Ok(())
+    }
+
+    /// Clear the device and set its brightness
+    pub fn init(&mut self) -> anyhow::Result<()> {
+        print_error(self.lp.clear());
+        self.flush_brightness()?;
+        Ok(())
+    }
+
+    fn flush_brightness(&mut self) -> anyhow::Result<()> {
+        if self.lp.supports_brightness() {
+            println!("Brightness {}", self.prefs.brightness);
+            print_error(self.lp.set_brightness(self.prefs.brightness));
+        }
+        Ok(())
+    }
+
+    /// Start loading the page at the given URL (or file name)
+    pub fn navigate(&mut self, url: &str) -> anyhow::Result<()> {
+        self.navigator.open(url)?;
+        self.update_buttons()?;
+        Ok(())
+    }
+
+    /// Start loading the page that was open when restpad last exited
+    pub fn resume(&mut self) -> anyhow::Result<()> {
+        // An unreadable session is no reason to refuse to start
+        let session: Session = DiskPersist::init("restpad-session")?
+            .read()
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        let Some(navigation) = session.navigation else {
+            bail!("No URL given, and there is no previous page to resume.");
+        };
+
+        self.navigator.restore(&navigation)?;
+        self.restore_y_scroll = Some(session.y_scroll);
+        self.update_buttons()?;
+        Ok(())
+    }
+
+    /// Handle button presses, page loads and timers until an error occurs
+    pub async fn main_loop(&mut self) -> anyhow::Result<()> {
+        loop {
+            select! {
+                Some(m) = self.lp.receiver().recv() => {
+                    print_error(self.handle_message(m));
+                }
+                result = self.navigator.finish_load() => {
+                    self.load_failed = print_error(result).is_none();
+                    print_error(self.navigator.save_cookies());
+                    if self.load_failed {
+                        self.on_load_failed();
+                    } else {
+                        self.on_page_load();
+                        print_error(self.save_session());
+                    }
+                    print_error(self.update_buttons());
+                }
+                Some(_) = self.frames.next_frame() => {
+                    print_error(self.update_buttons());
+                }
+                Some(_) = await_optional(&mut self.refresh_timer) => {
+                    print_error(self.refresh_on_timer());
+                }
+            };
+        }
+    }
+
+    fn handle_message(&mut self, message: InputMessage) -> anyhow::Result<()> {
+        match message {
+            InputMessage::Press(button) => {
+                self.pressed_buttons.insert(button);
+            }
+            InputMessage::Release(button) => {
+                self.pressed_buttons.remove(&button);
+
+                let scrollable = self.pad_state().scrollable_y_height() > 0;
+                match button {
+                    Button::UP => {
+                        if self.prefs.brightness < 8 {
+                            self.prefs.brightness += 1;
+                        }
+                        self.flush_brightness()?;
+                        self.save_settings()?;
+                    }
+                    Button::DOWN => {
+                        if self.prefs.brightness > 0 {
+                            self.prefs.brightness -= 1;
+                        }
+                        self.flush_brightness()?;
+                        self.save_settings()?;
+                    }
+                    Button::LEFT => {
+                        self.navigator.back();
+                    }
+                    Button::RIGHT => {
+                        self.navigator.forward();
+                    }
+                    Button::MIXER if self.pressed_buttons.contains(&Button::SESSION) => {
+                        // SESSION + MIXER bookmarks the current page
+                        self.session_chorded = true;
+                        self.toggle_bookmark()?;
+                    }
+                    Button::SESSION if self.session_chorded => {
+                        // The release of a chord doesn't count as a press
+                        self.session_chorded = false;
+                    }
+                    Button::SESSION => {
+                        self.navigator
+                            .navigate(&format!("restpad:{}", BOOKMARKS_PAGE))?;
+                    }
+                    Button::MIXER => {
+                        // While a page is loading, MIXER acts as the "stop" button
+                        if self.navigator.is_loading() {
+                            self.navigator.cancel();
+                        } else {
+                            self.navigator.refresh();
+                        }
+                    }
+                    Button::GridButton { x, y: 0 } if x == GRID_WIDTH as u8 && scrollable => {
+                        self.scroll_by(-1);
+                        self.save_session()?;
+                    }
+                    Button::GridButton { x, y }
+                        if x == GRID_WIDTH as u8 && y == GRID_HEIGHT as u8 - 1 && scrollable =>
+                    {
+                        self.scroll_by(1);
+                        self.save_session()?;
+                    }
+                    Button::GridButton { x, y } => {
+                        // Find the button that was pressed
+                        let action = self
+                            .pad_state()
+                            .find_button(Button::grid(x, y))
+                            .and_then(|button| button.on_press);
+                        match action {
+                            Some(Action::Navigate(Target::Href { href })) => {
+                                print_error(self.navigator.navigate(&href));
+                            }
+                            Some(Action::Navigate(Target::Page { page })) => {
+                                print_error(self.navigator.navigate_inline(&page));
+                            }
+                            Some(Action::Browser { href }) => {
+                                print_error(webbrowser::open(&href));
+                            }
+                            None => {}
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        self.update_buttons()?;
+        Ok(())
+    }
+
+    /// Add the current page to the bookmarks, or remove it if it's already there
+    fn toggle_bookmark(&mut self) -> anyhow::Result<()> {
+        let url = self.navigator.current_url();
+        if self.navigator.current().is_none() || url.scheme() == "restpad" {
+            return Ok(());
+        }
+
+        let url = url.to_string();
+        if let Some(i) = self.bookmarks.urls.iter().position(|b| *b == url) {
+            self.bookmarks.urls.remove(i);
+        } else {
+            self.bookmarks.urls.push(url);
+        }
+        self.navigator
+            .set_builtin_page(BOOKMARKS_PAGE, bookmarks_page(&self.bookmarks.urls));
+        self.save_bookmarks()?;
+        Ok(())
+    }
+
+    fn is_bookmarked(&self) -> bool {
+        let url = self.navigator.current_url().to_string();
+        self.bookmarks.urls.contains(&url)
+    }
+
+    /// Scroll the page by the given number of rows, staying within the page
+    fn scroll_by(&mut self, delta: i32) {
+        // The way we do pressed buttons, a pressed button will never be released
+        // if we adjust the scroll. So scrolling only works if no other buttons
+        // are pressed ^^
+        if !self.pressed_buttons.is_empty() {
+            return;
+        }
+        self.y_scroll = (self.y_scroll as i64 + delta as i64)
+            .clamp(0, self.pad_state().scrollable_y_height() as i64) as u32;
+    }
+
+    fn on_page_load(&mut self) {
+        self.frames.start(self.navigator.current());
+        self.arm_refresh_timer();
+        self.y_scroll = 0;
+        if let Some(y_scroll) = self.restore_y_scroll.take() {
+            self.y_scroll = y_scroll.min(self.pad_state().scrollable_y_height());
+        }
+    }
+
+    /// Keep showing the previous page the way it was
+    fn on_load_failed(&mut self) {
+        self.restore_y_scroll = None;
+        // A failed automatic refresh is tried again after the same time
+        if self.refresh_timer.is_none() {
+            self.arm_refresh_timer();
+        }
+    }
+
+    fn arm_refresh_timer(&mut self) {
+        self.refresh_timer = self
+            .navigator
+            .current()
+            .and_then(|payload| payload.refresh_after_secs)
+            .map(|refresh_secs| Box::pin(sleep(Duration::from_secs(refresh_secs as u64))));
+    }
+
+    fn refresh_on_timer(&mut self) -> anyhow::Result<()> {
+        // The timer is rearmed when the page has loaded. If the user is already
+        // loading another page, don't interrupt them.
+        self.refresh_timer = None;
+        if !self.navigator.is_loading() {
+            self.navigator.refresh();
+            self.update_buttons()?;
+        }
+        Ok(())
+    }
+
+    /// The state of everything that is shown on the pad
+    fn pad_state(&self) -> PadState<'_> {
+        PadState {
+            page: self.navigator.current(),
+            pressed_buttons: &self.pressed_buttons,
+            y_scroll: self.y_scroll,
+            frame: self.frames.frame(),
+            now: Utc::now(),
+            brightness: self.prefs.brightness,
+            has_history: self.navigator.has_history(),
+            has_future: self.navigator.has_future(),
+            is_loading: self.navigator.is_loading(),
+            load_failed: self.load_failed,
+            is_bookmarked: self.is_bookmarked(),
+        }
+    }
+
+    fn update_buttons(&mut self) -> anyhow::Result<()> {
+        let buttons = self.pad_state().calculate_buttons();
+
+        self.lp.set_all(buttons)?;
+        Ok(())
+    }
+}
+
+fn print_error<A, E: std::fmt::Display>(e: Result<A, E>) -> Option<A> {
+    match e {
+        Ok(x) => Some(x),
+        Err(x) => {
+            eprintln!("{}", x);
+            None
+        }
+    }
+}
+
+async fn await_optional(t: &mut Option<Pin<Box<tokio::time::Sleep>>>) -> Option<()> {
+    match t.as_mut() {
+        Some(timer) => {
+            timer.await;
+            Some(())
+        }
+        None => None,
+    }
+}
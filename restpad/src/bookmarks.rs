@@ -0,0 +1,36 @@
+use hex_color::HexColor;
+use restpad_sdk::layout::Grid;
+
+use crate::payload::{Action, ButtonSpec, Payload, TextSpec};
+
+/// Name of the generated bookmarks page, reachable at `restpad:bookmarks`
+pub static BOOKMARKS_PAGE: &str = "bookmarks";
+
+/// Colors for the bookmark pads, in order
+static COLORS: [HexColor; 6] = [
+    HexColor::rgb(0x33, 0x99, 0xFF),
+    HexColor::rgb(0x73, 0xD9, 0x87),
+    HexColor::rgb(0xFF, 0xCC, 0x00),
+    HexColor::rgb(0xCB, 0x5B, 0xDD),
+    HexColor::rgb(0xFF, 0x66, 0x33),
+    HexColor::rgb(0x00, 0xCC, 0xCC),
+];
+
+/// Generate the page that shows the given bookmarks as a grid of pads
+///
+/// Bookmarks are laid out left to right, top to bottom, and navigate to the
+/// bookmarked URL when pressed.
+pub fn bookmarks_page(bookmarks: &[String]) -> Payload {
+    let buttons = Grid::full_width().place(bookmarks.iter().enumerate().map(|(i, url)| {
+        ButtonSpec::new(0, 0, COLORS[i % COLORS.len()]).on_press(Action::navigate(url))
+    }));
+
+    let page = Payload::new()
+        .buttons(buttons)
+        .default_press_color(HexColor::WHITE);
+    if bookmarks.is_empty() {
+        page.text(TextSpec::new(0, 1, "No bookmarks", HexColor::WHITE))
+    } else {
+        page
+    }
+}
@@ -0,0 +1,169 @@
+use std::fmt::Write;
+
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, Duration, FixedOffset, Local, NaiveDate, Offset, Utc,
+};
+use chrono_tz::Tz;
+
+use crate::{
+    embedded_gfx::{Glyphs, Typeface},
+    payload::{ClockSpec, CountdownSpec, TextSpec},
+};
+
+/// The text a clock shows at the given time
+///
+/// Clocks with an unknown timezone or an invalid format show `?`.
+pub fn clock_text(clock: &ClockSpec, now: DateTime<Utc>) -> TextSpec {
+    let text = match timezone_offset(&clock.timezone, now) {
+        Some(offset) if is_valid_format(&clock.format) => {
+            now.with_timezone(&offset).format(&clock.format).to_string()
+        }
+        _ => "?".to_string(),
+    };
+    TextSpec {
+        width: clock.width,
+        font: clock.font,
+        align: clock.align,
+        scroll: clock.scroll,
+        ..TextSpec::new(clock.x, clock.y, text, clock.color)
+    }
+}
+
+/// The widest text a clock can show, to check a page the same way whenever
+/// it's checked
+///
+/// This is the clock at 23:59:59 on a Wednesday in September, which have the
+/// longest names and the largest numbers.
+pub fn widest_clock_text(clock: &ClockSpec) -> TextSpec {
+    let widest = NaiveDate::from_ymd_opt(2025, 9, 24)
+        .and_then(|date| date.and_hms_opt(23, 59, 59))
+        .expect("valid date")
+        .and_utc();
+    let offset = timezone_offset(&clock.timezone, widest).map_or(0, |o| o.local_minus_utc());
+    clock_text(clock, widest - Duration::seconds(offset.into()))
+}
+
+/// The text a countdown shows at the given time
+pub fn countdown_text(countdown: &CountdownSpec, now: DateTime<Utc>) -> TextSpec {
+    let left = countdown.to.with_timezone(&Utc) - now;
+    // Round up, so the countdown only shows zero once it's over
+    let seconds = (left.num_milliseconds().max(0) as u64).div_ceil(1000);
+    let text = match &countdown.finished_text {
+        Some(finished_text) if seconds == 0 => finished_text.clone(),
+        _ => format_duration(seconds, countdown.format.as_deref()),
+    };
+    countdown_spec(countdown, text)
+}
+
+/// The widest text a countdown can show: its finished text, or zeros in every
+/// field of its format
+pub fn widest_countdown_text(countdown: &CountdownSpec, glyphs: &Glyphs) -> TextSpec {
+    let typeface = Typeface {
+        font: countdown.font,
+        glyphs,
+    };
+    let zeros = format_duration(0, Some(countdown.format.as_deref().unwrap_or("%H:%M:%S")));
+    let text = countdown
+        .finished_text
+        .iter()
+        .cloned()
+        .chain([zeros])
+        .max_by_key(|text| typeface.text_width(text))
+        .unwrap_or_default();
+    countdown_spec(countdown, text)
+}
+
+fn countdown_spec(countdown: &CountdownSpec, text: String) -> TextSpec {
+    TextSpec {
+        width: countdown.width,
+        font: countdown.font,
+        align: countdown.align,
+        scroll: countdown.scroll,
+        ..TextSpec::new(countdown.x, countdown.y, text, countdown.color)
+    }
+}
+
+/// The offset from UTC of a timezone at the given time: `local`, `UTC`, a name
+/// like `Europe/Amsterdam`, or a fixed offset like `+02:00`
+pub fn timezone_offset(timezone: &str, now: DateTime<Utc>) -> Option<FixedOffset> {
+    match timezone {
+        "local" => Some(*now.with_timezone(&Local).offset()),
+        "UTC" | "utc" | "Z" => FixedOffset::east_opt(0),
+        _ => match timezone.parse::<Tz>() {
+            Ok(tz) => Some(now.with_timezone(&tz).offset().fix()),
+            Err(_) => timezone.parse().ok(),
+        },
+    }
+}
+
+/// Whether a clock format only has specifiers that `strftime` knows
+pub fn is_valid_format(format: &str) -> bool {
+    !StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+}
+
+/// Fill in the hours, minutes and seconds of a countdown format
+fn format_duration(seconds: u64, format: Option<&str>) -> String {
+    let format = format.unwrap_or(if seconds >= 3600 { "%H:%M:%S" } else { "%M:%S" });
+    let hours = seconds / 3600;
+    let minutes = if format.contains("%H") {
+        seconds / 60 % 60
+    } else {
+        seconds / 60
+    };
+
+    let mut text = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            text.push(c);
+            continue;
+        }
+        let _ = match chars.next() {
+            Some('H') => write!(text, "{:02}", hours),
+            Some('M') => write!(text, "{:02}", minutes),
+            Some('S') => write!(text, "{:02}", seconds % 60),
+            Some('%') => write!(text, "%"),
+            Some(other) => write!(text, "%{}", other),
+            None => write!(text, "%"),
+        };
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> DateTime<Utc> {
+        time.parse().unwrap()
+    }
+
+    #[test]
+    fn timezone_offsets() {
+        let summer = at("2024-07-01T12:00:00Z");
+        let winter = at("2024-01-01T12:00:00Z");
+        let hours = |timezone, now| {
+            timezone_offset(timezone, now).map(|offset| offset.local_minus_utc() / 3600)
+        };
+        assert_eq!(hours("UTC", summer), Some(0));
+        assert_eq!(hours("+02:00", winter), Some(2));
+        assert_eq!(hours("-05:00", summer), Some(-5));
+        assert_eq!(hours("Europe/Amsterdam", summer), Some(2));
+        assert_eq!(hours("Europe/Amsterdam", winter), Some(1));
+        assert_eq!(hours("America/New_York", winter), Some(-5));
+        assert_eq!(hours("Mars/Olympus_Mons", summer), None);
+        assert!(timezone_offset("local", summer).is_some());
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(format_duration(0, None), "00:00");
+        assert_eq!(format_duration(59 * 60 + 59, None), "59:59");
+        assert_eq!(format_duration(3600 + 2 * 60 + 3, None), "01:02:03");
+        assert_eq!(format_duration(100 * 3600, None), "100:00:00");
+        // Without hours, minutes keep counting past an hour
+        assert_eq!(format_duration(2 * 3600 + 30, Some("%M:%S")), "120:30");
+        assert_eq!(format_duration(90, Some("%S%% %x")), "30% %x");
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufReader, BufWriter, ErrorKind, Write},
+    os::unix::fs::OpenOptionsExt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Context};
+use cookie_store::CookieStore;
+use disk_persist::DiskPersist;
+use reqwest_cookie_store::CookieStoreMutex;
+
+use crate::preferences::Preferences;
+
+/// A cookie jar that is persisted to disk, next to the preferences
+pub struct CookieJar {
+    store: Arc<CookieStoreMutex>,
+    path: PathBuf,
+}
+
+impl CookieJar {
+    /// Load the cookie jar from disk
+    ///
+    /// Starts empty if it doesn't exist yet, or can't be read; losing cookies
+    /// is better than not starting at all.
+    pub fn load() -> anyhow::Result<CookieJar> {
+        let path = DiskPersist::<Preferences>::init("restpad")?
+            .path()
+            .with_file_name("restpad-cookies.json");
+
+        let store = read_store(&path).unwrap_or_else(|e| {
+            eprintln!("{:#}; starting without cookies", e);
+            CookieStore::default()
+        });
+
+        Ok(CookieJar {
+            store: Arc::new(CookieStoreMutex::new(store)),
+            path,
+        })
+    }
+
+    /// The cookie store to hand to the HTTP client
+    pub fn provider(&self) -> Arc<CookieStoreMutex> {
+        self.store.clone()
+    }
+
+    /// Write the cookie jar to disk
+    ///
+    /// The jar is written to a temporary file that only the user can read, and
+    /// then moved into place, so a crash never leaves half a jar behind.
+    pub fn save(&self) -> anyhow::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let temp_path = self.path.with_extension("json.tmp");
+        match fs::remove_file(&temp_path) {
+            Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&temp_path)
+            .with_context(|| format!("Failed to create {}", temp_path.display()))?;
+        let mut writer = BufWriter::new(file);
+
+        let store = self
+            .store
+            .lock()
+            .map_err(|_| anyhow!("Cookie store lock poisoned"))?;
+        // Session cookies are saved as well; restpad is long-running, so a restart
+        // should not log the user out. Expired cookies are of no use anymore.
+        for cookie in store.iter_unexpired() {
+            writeln!(writer, "{}", serde_json::to_string(cookie)?)?;
+        }
+        drop(store);
+
+        writer.into_inner()?.sync_all()?;
+        fs::rename(&temp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+/// Read a cookie jar, leaving out the cookies that have expired
+fn read_store(path: &Path) -> anyhow::Result<CookieStore> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(CookieStore::default()),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to open {}", path.display()));
+        }
+    };
+    CookieStore::load_json(BufReader::new(file))
+        .map_err(|e| anyhow!(e))
+        .with_context(|| format!("Failed to read cookies from {}", path.display()))
+}
@@ -1,4 +1,6 @@
-use embedded_graphics::mono_font::ascii::FONT_4X6;
+use std::collections::BTreeMap;
+
+use embedded_graphics::mono_font::iso_8859_1::{FONT_4X6, FONT_5X7};
 use embedded_graphics::mono_font::MonoTextStyle;
 use embedded_graphics::text::Baseline;
 use embedded_graphics::{
@@ -9,7 +11,79 @@ use embedded_graphics::{
     Drawable, Pixel,
 };
 
-use crate::any_launchpad::{Button, Buttons, RgbColor as OurRgbColor};
+use crate::{
+    any_launchpad::{Button, Buttons, RgbColor as OurRgbColor},
+    fonts::{symbol, FONT_3X5},
+    payload::Font,
+    GRID_HEIGHT, GRID_WIDTH,
+};
+use unicode_normalization::UnicodeNormalization;
+
+/// Glyphs that a page defines, by character
+pub type Glyphs = BTreeMap<char, Vec<String>>;
+
+/// A font, together with the glyphs that a page defines
+#[derive(Clone, Copy)]
+pub struct Typeface<'a> {
+    pub font: Font,
+    pub glyphs: &'a Glyphs,
+}
+
+/// How a character is drawn
+enum Glyph<'a> {
+    /// A character of the font
+    Font(char),
+
+    /// Rows of pixels from top to bottom, where `#` is lit
+    Bitmap(Vec<&'a str>),
+}
+
+impl<'a> Typeface<'a> {
+    /// Number of pads the given text takes up horizontally
+    pub fn text_width(&self, text: &str) -> u32 {
+        text.chars().map(|c| self.advance(&self.glyph(c))).sum()
+    }
+
+    /// The glyph for a character: the page's own glyph, the font's, a built-in
+    /// symbol, or the font's glyph for the character without accents, in that
+    /// order
+    fn glyph(&self, c: char) -> Glyph<'a> {
+        if let Some(rows) = self.glyphs.get(&c) {
+            return Glyph::Bitmap(rows.iter().map(String::as_str).collect());
+        }
+        if self.has_char(c) {
+            return Glyph::Font(c);
+        }
+        if let Some(rows) = symbol(c) {
+            return Glyph::Bitmap(rows.to_vec());
+        }
+        match c.nfd().next() {
+            Some(base) if self.has_char(base) => Glyph::Font(base),
+            _ => Glyph::Font('?'),
+        }
+    }
+
+    fn has_char(&self, c: char) -> bool {
+        match self.font {
+            Font::Small => (' '..='~').contains(&c),
+            Font::Normal | Font::Large | Font::Bold => {
+                (' '..='~').contains(&c) || ('\u{a0}'..='ÿ').contains(&c)
+            }
+        }
+    }
+
+    /// Number of pads from the start of a glyph to the start of the next one
+    fn advance(&self, glyph: &Glyph) -> u32 {
+        match glyph {
+            Glyph::Font(_) => self.font.char_width(),
+            Glyph::Bitmap(rows) => {
+                let width = rows.iter().map(|row| row.chars().count()).max();
+                let bold = (self.font == Font::Bold) as u32;
+                width.unwrap_or(0) as u32 + 1 + bold
+            }
+        }
+    }
+}
 
 pub struct PadTarget<'a> {
     buttons: &'a mut Buttons,
@@ -32,17 +106,64 @@ pub fn draw_text(
     text: &str,
     origin: (i32, i32),
     size: (u32, u32),
-    x_shift: i32,
+    offset: (i32, i32),
     color: OurRgbColor,
+    typeface: Typeface,
 ) {
     let mut target = PadTarget::new(buttons, origin, size);
-    let char_style = MonoTextStyle::new(&FONT_4X6, color.into());
-    let _ = Text::with_baseline(text, Point::new(x_shift, 0), char_style, Baseline::Top)
-        .draw(&mut target);
+    let (mono_font, passes) = match typeface.font {
+        Font::Small => (&FONT_3X5, 1),
+        Font::Normal => (&FONT_4X6, 1),
+        Font::Large => (&FONT_5X7, 1),
+        // Bold text is drawn twice, one pixel apart; `Font::char_width` leaves
+        // room for that
+        Font::Bold => (&FONT_4X6, 2),
+    };
+    let char_style = MonoTextStyle::new(mono_font, color.into());
+
+    let mut x = offset.0;
+    for c in text.chars() {
+        let glyph = typeface.glyph(c);
+        for pass in 0..passes {
+            let position = Point::new(x + pass, offset.1);
+            match &glyph {
+                Glyph::Font(c) => {
+                    let mut buffer = [0; 4];
+                    let c = c.encode_utf8(&mut buffer);
+                    let _ = Text::with_baseline(c, position, char_style, Baseline::Top)
+                        .draw(&mut target);
+                }
+                Glyph::Bitmap(rows) => {
+                    let pixels = rows.iter().enumerate().flat_map(|(y, row)| {
+                        row.chars()
+                            .enumerate()
+                            .filter(|(_, pixel)| *pixel == '#')
+                            .map(move |(x, _)| {
+                                Pixel(position + Point::new(x as i32, y as i32), color.into())
+                            })
+                    });
+                    let _ = target.draw_iter(pixels);
+                }
+            }
+        }
+        x += typeface.advance(&glyph) as i32;
+    }
 }
 
-pub fn text_width(text: &str) -> usize {
-    text.chars().count() * 4
+/// Draw rows of pixels; `None` pixels leave the pads below them alone
+pub fn draw_image(
+    buttons: &mut Buttons,
+    pixels: &[Vec<Option<OurRgbColor>>],
+    origin: (i32, i32),
+    size: (u32, u32),
+) {
+    let mut target = PadTarget::new(buttons, origin, size);
+    let pixels = pixels.iter().enumerate().flat_map(|(y, row)| {
+        row.iter().enumerate().filter_map(move |(x, color)| {
+            color.map(|color| Pixel(Point::new(x as i32, y as i32), color.into()))
+        })
+    });
+    let _ = target.draw_iter(pixels);
 }
 
 impl<'a> OriginDimensions for PadTarget<'a> {
@@ -59,13 +180,24 @@ impl<'a> DrawTarget for PadTarget<'a> {
     where
         I: IntoIterator<Item = Pixel<Self::Color>>,
     {
+        let in_size = |coord: i32, size: u32| u32::try_from(coord).is_ok_and(|c| c < size);
+        // Pixels that fall off the grid are left out rather than wrapped around
+        let grid = |coord: i32, origin: i32, limit: u32| {
+            coord
+                .checked_add(origin)
+                .and_then(|c| u8::try_from(c).ok())
+                .filter(|&c| u32::from(c) < limit)
+        };
         for Pixel(coord, color) in pixels.into_iter() {
-            if coord.x < self.size.0 as i32 && coord.y < self.size.1 as i32 {
+            if !(in_size(coord.x, self.size.0) && in_size(coord.y, self.size.1)) {
+                continue;
+            }
+            if let (Some(x), Some(y)) = (
+                grid(coord.x, self.origin.0, GRID_WIDTH),
+                grid(coord.y, self.origin.1, GRID_HEIGHT),
+            ) {
                 self.buttons.insert(
-                    Button::grid(
-                        (coord.x + self.origin.0) as u8,
-                        (coord.y + self.origin.1) as u8,
-                    ),
+                    Button::grid(x, y),
                     OurRgbColor::new(color.r(), color.g(), color.b()).into(),
                 );
             }
@@ -0,0 +1,183 @@
+//! Fonts for the text on the grid, on top of the ones embedded-graphics ships
+
+use embedded_graphics::{
+    image::ImageRaw,
+    mono_font::{mapping::ASCII, DecorationDimensions, MonoFont},
+    prelude::Size,
+};
+
+/// 3x5 pixel font with the printable ASCII characters
+pub const FONT_3X5: MonoFont = MonoFont {
+    image: ImageRaw::new(&IMAGE_3X5, GLYPHS_PER_ROW as u32 * 3),
+    glyph_mapping: &ASCII,
+    character_size: Size::new(3, 5),
+    character_spacing: 1,
+    baseline: 4,
+    underline: DecorationDimensions::new(5, 1),
+    strikethrough: DecorationDimensions::new(2, 1),
+};
+
+/// Glyphs for common symbols that aren't in the fonts, as rows of pixels from
+/// top to bottom where `#` is lit
+static SYMBOLS: &[(char, &[&str])] = &[
+    ('←', &["..#..", ".#...", "#####", ".#...", "..#.."]),
+    ('↑', &["..#..", ".###.", "#.#.#", "..#..", "..#.."]),
+    ('→', &["..#..", "...#.", "#####", "...#.", "..#.."]),
+    ('↓', &["..#..", "..#..", "#.#.#", ".###.", "..#.."]),
+    ('✓', &[".....", "....#", "...#.", "#.#..", ".#..."]),
+    ('✔', &[".....", "....#", "...#.", "#.#..", ".#..."]),
+    ('✗', &["#...#", ".#.#.", "..#..", ".#.#.", "#...#"]),
+    ('✘', &["#...#", ".#.#.", "..#..", ".#.#.", "#...#"]),
+    ('•', &["..", "..", "##", "##", ".."]),
+    ('…', &[".....", ".....", ".....", ".....", "#.#.#"]),
+    ('°', &[".#.", "#.#", ".#.", "...", "..."]),
+    ('€', &[".###", "#...", "###.", "#...", ".###"]),
+    ('♥', &[".#.#.", "#####", "#####", ".###.", "..#.."]),
+    ('★', &["..#..", "#####", ".###.", ".#.#.", "#...#"]),
+    ('♪', &["..##", "..#.", "..#.", "###.", "###."]),
+    ('⚡', &["..##", ".##.", "####", ".##.", "##.."]),
+    ('▲', &[".....", "..#..", ".###.", "#####", "....."]),
+    ('▼', &[".....", "#####", ".###.", "..#..", "....."]),
+    ('▶', &["#..", "##.", "###", "##.", "#.."]),
+    ('◀', &["..#", ".##", "###", ".##", "..#"]),
+    ('■', &["....", "####", "####", "####", "####"]),
+    ('●', &[".###.", "#####", "#####", "#####", ".###."]),
+];
+
+/// The built-in glyph for a symbol, if there is one
+pub fn symbol(c: char) -> Option<&'static [&'static str]> {
+    SYMBOLS
+        .iter()
+        .find(|(symbol, _)| *symbol == c)
+        .map(|(_, rows)| *rows)
+}
+
+/// Number of glyphs next to each other in the image of a font
+const GLYPHS_PER_ROW: usize = 16;
+
+/// The glyphs of [`FONT_3X5`] for the characters from `' '` to DEL, as rows of
+/// three pixels from top to bottom
+const GLYPHS_3X5: [[u8; 5]; 96] = [
+    [0b000, 0b000, 0b000, 0b000, 0b000], // ' '
+    [0b010, 0b010, 0b010, 0b000, 0b010], // '!'
+    [0b101, 0b101, 0b000, 0b000, 0b000], // '"'
+    [0b101, 0b111, 0b101, 0b111, 0b101], // '#'
+    [0b011, 0b110, 0b010, 0b011, 0b110], // '$'
+    [0b101, 0b001, 0b010, 0b100, 0b101], // '%'
+    [0b110, 0b110, 0b111, 0b101, 0b011], // '&'
+    [0b010, 0b010, 0b000, 0b000, 0b000], // '\''
+    [0b001, 0b010, 0b010, 0b010, 0b001], // '('
+    [0b100, 0b010, 0b010, 0b010, 0b100], // ')'
+    [0b000, 0b101, 0b010, 0b101, 0b000], // '*'
+    [0b000, 0b010, 0b111, 0b010, 0b000], // '+'
+    [0b000, 0b000, 0b000, 0b010, 0b100], // ','
+    [0b000, 0b000, 0b111, 0b000, 0b000], // '-'
+    [0b000, 0b000, 0b000, 0b000, 0b010], // '.'
+    [0b001, 0b001, 0b010, 0b100, 0b100], // '/'
+    [0b111, 0b101, 0b101, 0b101, 0b111], // '0'
+    [0b010, 0b110, 0b010, 0b010, 0b111], // '1'
+    [0b110, 0b001, 0b010, 0b100, 0b111], // '2'
+    [0b110, 0b001, 0b010, 0b001, 0b110], // '3'
+    [0b101, 0b101, 0b111, 0b001, 0b001], // '4'
+    [0b111, 0b100, 0b110, 0b001, 0b110], // '5'
+    [0b011, 0b100, 0b111, 0b101, 0b111], // '6'
+    [0b111, 0b001, 0b010, 0b100, 0b100], // '7'
+    [0b111, 0b101, 0b111, 0b101, 0b111], // '8'
+    [0b111, 0b101, 0b111, 0b001, 0b110], // '9'
+    [0b000, 0b010, 0b000, 0b010, 0b000], // ':'
+    [0b000, 0b010, 0b000, 0b010, 0b100], // ';'
+    [0b001, 0b010, 0b100, 0b010, 0b001], // '<'
+    [0b000, 0b111, 0b000, 0b111, 0b000], // '='
+    [0b100, 0b010, 0b001, 0b010, 0b100], // '>'
+    [0b111, 0b001, 0b010, 0b000, 0b010], // '?'
+    [0b010, 0b101, 0b111, 0b100, 0b011], // '@'
+    [0b010, 0b101, 0b111, 0b101, 0b101], // 'A'
+    [0b110, 0b101, 0b110, 0b101, 0b110], // 'B'
+    [0b011, 0b100, 0b100, 0b100, 0b011], // 'C'
+    [0b110, 0b101, 0b101, 0b101, 0b110], // 'D'
+    [0b111, 0b100, 0b111, 0b100, 0b111], // 'E'
+    [0b111, 0b100, 0b111, 0b100, 0b100], // 'F'
+    [0b011, 0b100, 0b111, 0b101, 0b011], // 'G'
+    [0b101, 0b101, 0b111, 0b101, 0b101], // 'H'
+    [0b111, 0b010, 0b010, 0b010, 0b111], // 'I'
+    [0b001, 0b001, 0b001, 0b101, 0b010], // 'J'
+    [0b101, 0b101, 0b110, 0b101, 0b101], // 'K'
+    [0b100, 0b100, 0b100, 0b100, 0b111], // 'L'
+    [0b101, 0b111, 0b111, 0b101, 0b101], // 'M'
+    [0b101, 0b111, 0b111, 0b111, 0b101], // 'N'
+    [0b010, 0b101, 0b101, 0b101, 0b010], // 'O'
+    [0b110, 0b101, 0b110, 0b100, 0b100], // 'P'
+    [0b010, 0b101, 0b101, 0b111, 0b011], // 'Q'
+    [0b110, 0b101, 0b111, 0b110, 0b101], // 'R'
+    [0b011, 0b100, 0b010, 0b001, 0b110], // 'S'
+    [0b111, 0b010, 0b010, 0b010, 0b010], // 'T'
+    [0b101, 0b101, 0b101, 0b101, 0b011], // 'U'
+    [0b101, 0b101, 0b101, 0b010, 0b010], // 'V'
+    [0b101, 0b101, 0b111, 0b111, 0b101], // 'W'
+    [0b101, 0b101, 0b010, 0b101, 0b101], // 'X'
+    [0b101, 0b101, 0b010, 0b010, 0b010], // 'Y'
+    [0b111, 0b001, 0b010, 0b100, 0b111], // 'Z'
+    [0b111, 0b100, 0b100, 0b100, 0b111], // '['
+    [0b100, 0b100, 0b010, 0b001, 0b001], // '\\'
+    [0b111, 0b001, 0b001, 0b001, 0b111], // ']'
+    [0b010, 0b101, 0b000, 0b000, 0b000], // '^'
+    [0b000, 0b000, 0b000, 0b000, 0b111], // '_'
+    [0b100, 0b010, 0b000, 0b000, 0b000], // '`'
+    [0b000, 0b110, 0b011, 0b101, 0b111], // 'a'
+    [0b100, 0b110, 0b101, 0b101, 0b110], // 'b'
+    [0b000, 0b011, 0b100, 0b100, 0b011], // 'c'
+    [0b001, 0b011, 0b101, 0b101, 0b011], // 'd'
+    [0b000, 0b011, 0b101, 0b110, 0b011], // 'e'
+    [0b001, 0b010, 0b111, 0b010, 0b010], // 'f'
+    [0b011, 0b101, 0b011, 0b001, 0b110], // 'g'
+    [0b100, 0b110, 0b101, 0b101, 0b101], // 'h'
+    [0b010, 0b000, 0b010, 0b010, 0b010], // 'i'
+    [0b001, 0b000, 0b001, 0b101, 0b010], // 'j'
+    [0b100, 0b101, 0b110, 0b110, 0b101], // 'k'
+    [0b110, 0b010, 0b010, 0b010, 0b111], // 'l'
+    [0b000, 0b111, 0b111, 0b111, 0b101], // 'm'
+    [0b000, 0b110, 0b101, 0b101, 0b101], // 'n'
+    [0b000, 0b010, 0b101, 0b101, 0b010], // 'o'
+    [0b000, 0b110, 0b101, 0b110, 0b100], // 'p'
+    [0b000, 0b011, 0b101, 0b011, 0b001], // 'q'
+    [0b000, 0b011, 0b100, 0b100, 0b100], // 'r'
+    [0b000, 0b011, 0b110, 0b011, 0b110], // 's'
+    [0b010, 0b111, 0b010, 0b010, 0b011], // 't'
+    [0b000, 0b101, 0b101, 0b101, 0b011], // 'u'
+    [0b000, 0b101, 0b101, 0b111, 0b010], // 'v'
+    [0b000, 0b101, 0b111, 0b111, 0b111], // 'w'
+    [0b000, 0b101, 0b010, 0b010, 0b101], // 'x'
+    [0b101, 0b101, 0b011, 0b001, 0b110], // 'y'
+    [0b000, 0b111, 0b011, 0b110, 0b111], // 'z'
+    [0b011, 0b010, 0b110, 0b010, 0b011], // '{'
+    [0b010, 0b010, 0b010, 0b010, 0b010], // '|'
+    [0b110, 0b010, 0b011, 0b010, 0b110], // '}'
+    [0b000, 0b011, 0b110, 0b000, 0b000], // '~'
+    [0b000, 0b000, 0b000, 0b000, 0b000], // DEL
+];
+
+const IMAGE_3X5: [u8; 180] = pack_3x5(&GLYPHS_3X5);
+
+/// Lay out glyphs in the one bit per pixel image that [`ImageRaw`] expects
+const fn pack_3x5(glyphs: &[[u8; 5]; 96]) -> [u8; 180] {
+    let bytes_per_line = GLYPHS_PER_ROW * 3 / 8;
+    let mut image = [0; 180];
+    let mut glyph = 0;
+    while glyph < glyphs.len() {
+        let mut row = 0;
+        while row < 5 {
+            let line = (glyph / GLYPHS_PER_ROW * 5 + row) * bytes_per_line;
+            let mut column = 0;
+            while column < 3 {
+                if glyphs[glyph][row] & (0b100 >> column) != 0 {
+                    let x = glyph % GLYPHS_PER_ROW * 3 + column;
+                    image[line + x / 8] |= 0x80 >> (x % 8);
+                }
+                column += 1;
+            }
+            row += 1;
+        }
+        glyph += 1;
+    }
+    image
+}
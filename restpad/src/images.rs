@@ -0,0 +1,207 @@
+use std::io::Cursor;
+
+use anyhow::bail;
+use gif::DisposalMethod;
+use hex_color::HexColor;
+
+use crate::{
+    payload::{ImageFrame, ImageSource, ImageSpec},
+    render::{fit_pixels, image_size},
+};
+
+/// Rows of pixels, from top to bottom; `None` pixels are transparent
+type Pixels = Vec<Vec<Option<HexColor>>>;
+
+/// The largest width and height of an image file, in pixels
+///
+/// Images are scaled down to the grid anyway, and a file that claims to be huge
+/// would otherwise make us allocate more memory than there is.
+static MAX_IMAGE_SIZE: u32 = 1024;
+
+/// Replace the `src` of an image with the frames decoded from the given PNG or
+/// GIF file
+///
+/// Every frame is scaled or cropped as soon as it is decoded, so only small
+/// frames are kept, and images that are much larger than the grid don't have to
+/// be scaled again on every frame.
+pub fn resolve(image: &mut ImageSpec, contents: &[u8]) -> anyhow::Result<()> {
+    // All frames have the size of the first one
+    let mut size = None;
+    let frames = decode(contents, |pixels| {
+        let (width, height) = *size.get_or_insert_with(|| image_size(image, pixels));
+        fit_pixels(pixels, width, height, image.fit)
+    })?;
+    let Some((width, height)) = size.filter(|_| !frames.is_empty()) else {
+        bail!("Image has no frames");
+    };
+
+    image.width = Some(width);
+    image.height = Some(height);
+    image.source = ImageSource::Frames { frames };
+    Ok(())
+}
+
+/// Scale or crop the pixels of an image that the page gives inline to its size
+///
+/// This is done once when the page loads, like for images from a `src`, so
+/// the pixels are small and don't have to be scaled on every frame.
+pub fn fit(image: &mut ImageSpec) {
+    let first = match &image.source {
+        ImageSource::Pixels { pixels } => pixels,
+        ImageSource::Frames { frames } => match frames.first() {
+            Some(frame) => &frame.pixels,
+            None => return,
+        },
+        ImageSource::Src { .. } => return,
+    };
+    // All frames get the size of the first one
+    let (width, height) = image_size(image, first);
+    let fit = image.fit;
+    match &mut image.source {
+        ImageSource::Pixels { pixels } => *pixels = fit_pixels(pixels, width, height, fit),
+        ImageSource::Frames { frames } => {
+            for frame in frames {
+                frame.pixels = fit_pixels(&frame.pixels, width, height, fit);
+            }
+        }
+        ImageSource::Src { .. } => {}
+    }
+    image.width = Some(width);
+    image.height = Some(height);
+}
+
+/// Decode a PNG or GIF file, telling them apart by their signature, and `fit`
+/// the pixels of every frame
+fn decode(
+    contents: &[u8],
+    mut fit: impl FnMut(&Pixels) -> Pixels,
+) -> anyhow::Result<Vec<ImageFrame>> {
+    if contents.starts_with(b"\x89PNG") {
+        let pixels = decode_png(contents)?;
+        Ok(vec![ImageFrame {
+            pixels: fit(&pixels),
+            delay_ms: 0,
+        }])
+    } else if contents.starts_with(b"GIF8") {
+        decode_gif(contents, fit)
+    } else {
+        bail!("Unsupported image format; only PNG and GIF are supported")
+    }
+}
+
+fn decode_png(contents: &[u8]) -> anyhow::Result<Pixels> {
+    let mut decoder = png::Decoder::new(Cursor::new(contents));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    check_size(reader.info().width, reader.info().height)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+
+    let channels = info.color_type.samples();
+    let pixels = buffer[..info.buffer_size()]
+        .chunks(info.line_size)
+        .map(|line| {
+            line.chunks(channels)
+                .take(info.width as usize)
+                .map(|p| match info.color_type {
+                    png::ColorType::Grayscale => Some(HexColor::rgb(p[0], p[0], p[0])),
+                    png::ColorType::GrayscaleAlpha => {
+                        opaque(p[1]).then(|| HexColor::rgb(p[0], p[0], p[0]))
+                    }
+                    png::ColorType::Rgba => opaque(p[3]).then(|| HexColor::rgb(p[0], p[1], p[2])),
+                    _ => Some(HexColor::rgb(p[0], p[1], p[2])),
+                })
+                .collect()
+        })
+        .collect();
+    Ok(pixels)
+}
+
+fn decode_gif(
+    contents: &[u8],
+    mut fit: impl FnMut(&Pixels) -> Pixels,
+) -> anyhow::Result<Vec<ImageFrame>> {
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::RGBA);
+    options.check_frame_consistency(true);
+    let mut decoder = options.read_info(Cursor::new(contents))?;
+    check_size(decoder.width().into(), decoder.height().into())?;
+
+    // Frames only update part of the image, so they're drawn onto a canvas
+    let (width, height) = (decoder.width() as usize, decoder.height() as usize);
+    let mut canvas: Pixels = vec![vec![None; width]; height];
+
+    let mut frames = vec![];
+    while let Some(frame) = decoder.read_next_frame()? {
+        let previous = (frame.dispose == DisposalMethod::Previous).then(|| canvas.clone());
+        let (left, top) = (frame.left as usize, frame.top as usize);
+        let rect = (top..(top + frame.height as usize).min(height))
+            .flat_map(|y| (left..(left + frame.width as usize).min(width)).map(move |x| (x, y)));
+
+        for (x, y) in rect.clone() {
+            let i = ((y - top) * frame.width as usize + (x - left)) * 4;
+            let p = &frame.buffer[i..i + 4];
+            if opaque(p[3]) {
+                canvas[y][x] = Some(HexColor::rgb(p[0], p[1], p[2]));
+            }
+        }
+        frames.push(ImageFrame {
+            pixels: fit(&canvas),
+            // GIF delays are in hundredths of a second
+            delay_ms: frame.delay as u32 * 10,
+        });
+
+        if let Some(previous) = previous {
+            canvas = previous;
+        } else if frame.dispose == DisposalMethod::Background {
+            for (x, y) in rect {
+                canvas[y][x] = None;
+            }
+        }
+    }
+    Ok(frames)
+}
+
+/// Refuse images larger than `MAX_IMAGE_SIZE` before decoding them
+fn check_size(width: u32, height: u32) -> anyhow::Result<()> {
+    if width > MAX_IMAGE_SIZE || height > MAX_IMAGE_SIZE {
+        bail!(
+            "Image is {}x{} pixels, but can be at most {}x{}",
+            width,
+            height,
+            MAX_IMAGE_SIZE,
+            MAX_IMAGE_SIZE
+        );
+    }
+    Ok(())
+}
+
+/// Whether a pixel with the given alpha is shown at all
+fn opaque(alpha: u8) -> bool {
+    alpha >= 0x80
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_gif() {
+        // A single transparent pixel
+        let gif = b"GIF89a\x01\x00\x01\x00\x80\x00\x00\xff\xff\xff\x00\x00\x00\
+            \x21\xf9\x04\x01\x00\x00\x00\x00\
+            \x2c\x00\x00\x00\x00\x01\x00\x01\x00\x00\x02\x02\x44\x01\x00\x3b";
+        let frames = decode(gif, Pixels::clone).unwrap();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].pixels, vec![vec![None]]);
+    }
+
+    #[test]
+    fn refuses_huge_gif() {
+        // Claims to be 65535x65535 pixels, but has a single pixel
+        let gif = b"GIF89a\xff\xff\xff\xff\x80\x00\x00\xff\xff\xff\x00\x00\x00\
+            \x2c\x00\x00\x00\x00\x01\x00\x01\x00\x00\x02\x02\x44\x01\x00\x3b";
+        let error = decode(gif, Pixels::clone).unwrap_err();
+        assert!(error.to_string().contains("at most 1024x1024"), "{}", error);
+    }
+}
@@ -0,0 +1,28 @@
+//! Show pages served over HTTP (or loaded from files and programs) on a
+//! Novation Launchpad
+//!
+//! Pages are described by [`payload::Payload`] (shared with the `restpad-sdk`
+//! crate), loaded by a [`navigator::Navigator`], turned into pad colors by
+//! [`render::PadState`] and sent to a device through the
+//! [`any_launchpad::Launchpad`] trait. [`RestPad`] ties all of these together.
+
+pub mod any_launchpad;
+mod app;
+mod bookmarks;
+mod clock;
+pub mod cookies;
+pub mod credentials;
+mod embedded_gfx;
+mod fonts;
+mod images;
+pub mod navigator;
+pub mod page_format;
+mod preferences;
+pub mod render;
+mod scheduler;
+mod unix_socket;
+pub mod validate;
+
+pub use app::RestPad;
+pub use restpad_sdk::layout::{GRID_HEIGHT, GRID_WIDTH};
+pub use restpad_sdk::{capabilities, payload};
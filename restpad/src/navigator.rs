@@ -1,15 +1,77 @@
-use std::{env, fs::File, io::Read};
+use std::{
+    collections::HashMap, env, fs, future::Future, os::unix::fs::PermissionsExt, path::PathBuf,
+    sync::Arc,
+};
 
-use anyhow::{bail, Context};
-use reqwest::{self, Url};
+use anyhow::{anyhow, bail, Context};
+use data_url::DataUrl;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use reqwest::{
+    self,
+    header::{HeaderMap, CONTENT_TYPE, LOCATION},
+    redirect, RequestBuilder, Url,
+};
+use serde::{Deserialize, Serialize};
+use tokio::{process::Command, select, sync::mpsc, task::JoinHandle};
 
-use crate::payload::Payload;
+use crate::{
+    capabilities::Capabilities,
+    cookies::CookieJar,
+    credentials::Credentials,
+    images,
+    page_format::PageFormat,
+    payload::{ImageSource, ImageSpec, Payload},
+    unix_socket,
+};
+
+/// How many redirects to follow before giving up on a request
+static MAX_REDIRECTS: usize = 10;
 
 pub struct Navigator {
     current_url: reqwest::Url,
     current_page: Option<Payload>,
     history: Vec<reqwest::Url>,
     future: Vec<reqwest::Url>,
+    pending: Option<PendingLoad>,
+    credentials: Arc<Credentials>,
+    client: reqwest::Client,
+    cookies: CookieJar,
+    builtin_pages: HashMap<String, Payload>,
+    watched: Option<WatchedFile>,
+    file_changes: (mpsc::UnboundedSender<()>, mpsc::UnboundedReceiver<()>),
+}
+
+/// The current URL and history of a `Navigator`, so it can be restored later
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NavigationState {
+    current_url: String,
+    history: Vec<String>,
+    future: Vec<String>,
+}
+
+/// How a page load should affect the history once it completes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LoadKind {
+    Navigate,
+    Back,
+    Forward,
+    Refresh,
+}
+
+/// A local file page that is reloaded when it changes
+struct WatchedFile {
+    _watcher: RecommendedWatcher,
+    url: reqwest::Url,
+    /// How to load the file again; a navigation that failed will be retried as-is
+    kind: LoadKind,
+}
+
+/// A page load that is running in the background
+struct PendingLoad {
+    url: reqwest::Url,
+    kind: LoadKind,
+    task: JoinHandle<anyhow::Result<Payload>>,
 }
 
 impl Navigator {
@@ -18,32 +80,259 @@ impl Navigator {
     /// The browser starts off with a base URL equal to the current working
     /// directory, so that it will be able to open relative files from the file
     /// system.
-    pub fn new() -> anyhow::Result<Navigator> {
+    ///
+    /// The given credentials are sent along with every HTTP request to their
+    /// host, and cookies are kept in the given cookie jar. Requests also announce the
+    /// capabilities of the client, so servers can adapt their pages.
+    pub fn new(credentials: Credentials, cookies: CookieJar) -> anyhow::Result<Navigator> {
         let cwd = env::current_dir()?;
         let current_url = reqwest::Url::parse(&format!("file://{}/", cwd.as_path().display()))?;
+        let client = reqwest::Client::builder()
+            .cookie_provider(cookies.provider())
+            // Redirects are followed in `get`, so credentials never go to another host
+            .redirect(redirect::Policy::none())
+            .build()?;
 
         Ok(Navigator {
             current_url,
             current_page: None,
             history: Default::default(),
             future: Default::default(),
+            pending: None,
+            credentials: Arc::new(credentials),
+            client,
+            cookies,
+            builtin_pages: Default::default(),
+            watched: None,
+            file_changes: mpsc::unbounded_channel(),
         })
     }
 
-    /// Navigate to the given URL, returning its payload if successful
-    pub async fn navigate(&mut self, url: &str) -> anyhow::Result<()> {
-        let target_url = self.current_url.join(url)?;
-        let old_url = self.do_navigate(target_url).await?;
-        self.history.push(old_url);
-        self.future.clear();
+    /// Start navigating to a URL that a page links to
+    ///
+    /// The page is loaded in the background; use `finish_load` to wait for it.
+    /// Any load that is already in progress is cancelled.
+    pub fn navigate(&mut self, url: &str) -> anyhow::Result<()> {
+        let target_url = self.resolve(url, false)?;
+        self.start_load(target_url, LoadKind::Navigate);
+        Ok(())
+    }
+
+    /// Start navigating to a URL that the user gave, like `navigate`
+    ///
+    /// Unlike links on pages, these can run programs from anywhere.
+    pub fn open(&mut self, url: &str) -> anyhow::Result<()> {
+        let target_url = self.resolve(url, true)?;
+        self.start_load(target_url, LoadKind::Navigate);
+        Ok(())
+    }
+
+    /// Start navigating to a page that is given inline
+    ///
+    /// The page is encoded as a `data:` URL, so it takes part in the history
+    /// like any other page.
+    pub fn navigate_inline(&mut self, page: &Payload) -> anyhow::Result<()> {
+        let json = serde_json::to_string(page)?;
+        let url = format!(
+            "data:application/json,{}",
+            utf8_percent_encode(&json, NON_ALPHANUMERIC)
+        );
+        self.navigate(&url)
+    }
+
+    /// Resolve a URL relative to the current page
+    ///
+    /// Only the user and local pages may run programs; `exec:` URLs on any
+    /// other page are refused.
+    fn resolve(&self, url: &str, from_user: bool) -> anyhow::Result<reqwest::Url> {
+        let base = local_base(self.base_url())?;
+        let target_url = base.join(url)?;
+        if target_url.scheme() != "exec" {
+            return Ok(target_url);
+        }
+        if !from_user && !self.is_local_page() {
+            bail!("Only local pages can run programs: {}", url);
+        }
+        if !target_url.cannot_be_a_base() {
+            if target_url.host().is_some() {
+                bail!("exec: URLs can't name a host: {}", url);
+            }
+            return Ok(target_url);
+        }
+
+        // `exec:script.sh` names a script relative to the current page, the same
+        // way a relative file name would.
+        let script = base.join(target_url.path())?;
+        let mut resolved = Url::parse(&format!("exec://{}", script.path()))?;
+        resolved.set_query(target_url.query());
+        Ok(resolved)
+    }
+
+    fn start_load(&mut self, url: reqwest::Url, kind: LoadKind) {
+        self.cancel();
+        // Other pages leave the watcher alone, so the file page on screen is
+        // still reloaded if they fail to load
+        if url.scheme() == "file" {
+            self.watch(&url, kind);
+        }
+        let task = tokio::spawn(self.load(url.clone()));
+        self.pending = Some(PendingLoad { url, kind, task });
+    }
+
+    /// Check that the page at the given URL, relative to the current page, can
+    /// be loaded, without navigating to it
+    ///
+    /// Programs are not run, since they could do anything; they only have to be
+    /// executable files.
+    pub async fn check_link(&self, url: &str) -> anyhow::Result<()> {
+        let url = self.resolve(url, false)?;
+        if url.scheme() == "exec" {
+            let program = program_path(&url)?;
+            let metadata = fs::metadata(&program)
+                .with_context(|| format!("Failed to open {}", program.display()))?;
+            if !metadata.is_file() || metadata.permissions().mode() & 0o111 == 0 {
+                bail!("{} is not an executable file", program.display());
+            }
+            return Ok(());
+        }
+        self.load(url).await?;
         Ok(())
     }
 
-    async fn do_navigate(&mut self, target_url: reqwest::Url) -> anyhow::Result<reqwest::Url> {
-        let payload: Payload = Self::load_url(target_url.clone()).await?;
-        let old_url = std::mem::replace(&mut self.current_url, target_url);
+    fn load(
+        &self,
+        url: reqwest::Url,
+    ) -> impl Future<Output = anyhow::Result<Payload>> + Send + 'static {
+        let builtin_page =
+            (url.scheme() == "restpad").then(|| self.builtin_pages.get(url.path()).cloned());
+        let client = self.client.clone();
+        let credentials = self.credentials.clone();
+        async move {
+            match builtin_page {
+                // Built-in pages are generated locally, they don't need loading
+                Some(page) => page.with_context(|| format!("No such page: {}", url)),
+                None => Self::load_url(client, credentials, url).await,
+            }
+        }
+    }
+
+    /// Wait for the next page load to complete, and make it the current page
+    ///
+    /// Page loads are started by the navigation functions, and also whenever
+    /// the local file page that is being shown changes on disk. This function
+    /// is cancel safe: if the returned future is dropped, the load keeps running
+    /// and can be awaited again later.
+    pub async fn finish_load(&mut self) -> anyhow::Result<()> {
+        let result = loop {
+            let file_changes = &mut self.file_changes.1;
+            match &mut self.pending {
+                Some(pending) => select! {
+                    result = &mut pending.task => break result,
+                    _ = file_changes.recv() => {}
+                },
+                None => {
+                    file_changes.recv().await;
+                }
+            }
+            self.reload_watched();
+        };
+        let pending = self.pending.take().expect("a load just finished");
+        let payload = result??;
+
+        // Only the page that is now on screen is watched
+        self.watch(&pending.url, LoadKind::Refresh);
+
+        let old_url = std::mem::replace(&mut self.current_url, pending.url);
         self.current_page = Some(payload);
-        Ok(old_url)
+        match pending.kind {
+            LoadKind::Navigate => {
+                self.history.push(old_url);
+                self.future.clear();
+            }
+            LoadKind::Back => {
+                self.history.pop();
+                self.future.push(old_url);
+            }
+            LoadKind::Forward => {
+                self.future.pop();
+                self.history.push(old_url);
+            }
+            LoadKind::Refresh => {}
+        }
+        Ok(())
+    }
+
+    /// Whether a page load is currently in progress
+    pub fn is_loading(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Abort the page load in progress, if any
+    pub fn cancel(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            pending.task.abort();
+            self.watch(&self.current_url.clone(), LoadKind::Refresh);
+        }
+    }
+
+    /// Start watching the given URL for changes, if it is a local file
+    ///
+    /// Any previously watched file is no longer watched.
+    fn watch(&mut self, url: &reqwest::Url, kind: LoadKind) {
+        if let Some(watched) = &mut self.watched {
+            if watched.url == *url {
+                watched.kind = kind;
+                return;
+            }
+        }
+
+        self.watched = None;
+        while self.file_changes.1.try_recv().is_ok() {}
+
+        let Ok(file_path) = url.to_file_path() else {
+            return;
+        };
+        let (Some(dir), Some(file_name)) = (file_path.parent(), file_path.file_name()) else {
+            return;
+        };
+
+        // Editors often save by replacing the file, so watch the directory
+        // and pick out the events for our file.
+        let file_name = file_name.to_owned();
+        let sender = self.file_changes.0.clone();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            let ours = event
+                .paths
+                .iter()
+                .any(|p| p.file_name() == Some(file_name.as_os_str()));
+            if ours && !matches!(event.kind, EventKind::Access(_)) {
+                let _ = sender.send(());
+            }
+        });
+
+        // Watching is a convenience: if it doesn't work, the page just doesn't
+        // reload by itself.
+        let Ok(mut watcher) = watcher else {
+            return;
+        };
+        if watcher.watch(dir, RecursiveMode::NonRecursive).is_err() {
+            return;
+        }
+        self.watched = Some(WatchedFile {
+            _watcher: watcher,
+            url: url.clone(),
+            kind,
+        });
+    }
+
+    /// Load the watched local file page again
+    fn reload_watched(&mut self) {
+        if let Some(watched) = &self.watched {
+            self.start_load(watched.url.clone(), watched.kind);
+        }
     }
 
     pub fn has_history(&self) -> bool {
@@ -57,53 +346,393 @@ impl Navigator {
         !self.future.is_empty()
     }
 
-    pub async fn back(&mut self) -> anyhow::Result<()> {
+    pub fn back(&mut self) {
         if !self.has_history() {
-            return Ok(());
+            return;
         }
-        let Some(prev) = self.history.pop() else {
-            return Ok(());
+        let Some(prev) = self.history.last() else {
+            return;
         };
-        let old_url = self.do_navigate(prev).await?;
-        self.future.push(old_url);
-        Ok(())
+        self.start_load(prev.clone(), LoadKind::Back);
     }
 
-    pub async fn forward(&mut self) -> anyhow::Result<()> {
-        let Some(next) = self.future.pop() else {
-            return Ok(());
+    pub fn forward(&mut self) {
+        let Some(next) = self.future.last() else {
+            return;
         };
-        let old_url = self.do_navigate(next).await?;
-        self.history.push(old_url);
-        Ok(())
+        self.start_load(next.clone(), LoadKind::Forward);
+    }
+
+    pub fn refresh(&mut self) {
+        self.start_load(self.current_url.clone(), LoadKind::Refresh);
+    }
+
+    /// The URL that relative URLs on the current page are resolved against
+    ///
+    /// Pages like `data:` URLs can't serve as a base, so relative URLs on them
+    /// are resolved against the page they were opened from instead.
+    fn base_url(&self) -> &reqwest::Url {
+        std::iter::once(&self.current_url)
+            .chain(self.history.iter().rev())
+            .find(|url| !url.cannot_be_a_base())
+            .unwrap_or(&self.current_url)
     }
 
-    pub async fn refresh(&mut self) -> anyhow::Result<()> {
-        self.do_navigate(self.current_url.clone()).await?;
+    /// Whether the current page comes from this machine
+    ///
+    /// Inline pages are `data:` URLs, which count as the page they were opened
+    /// from.
+    fn is_local_page(&self) -> bool {
+        let page = std::iter::once(&self.current_url)
+            .chain(self.history.iter().rev())
+            .find(|url| url.scheme() != "data")
+            .unwrap_or(&self.current_url);
+        matches!(page.scheme(), "file" | "exec" | "restpad")
+    }
+
+    /// Set the contents of a page generated by restpad itself, at `restpad:<name>`
+    pub fn set_builtin_page(&mut self, name: &str, page: Payload) {
+        self.builtin_pages.insert(name.to_string(), page);
+    }
+
+    /// The URL of the current page
+    pub fn current_url(&self) -> &Url {
+        &self.current_url
+    }
+
+    /// Take a snapshot of the current URL and history
+    pub fn state(&self) -> NavigationState {
+        NavigationState {
+            current_url: self.current_url.to_string(),
+            history: self.history.iter().map(Url::to_string).collect(),
+            future: self.future.iter().map(Url::to_string).collect(),
+        }
+    }
+
+    /// Restore a snapshot of the current URL and history, and start reloading the page
+    pub fn restore(&mut self, state: &NavigationState) -> anyhow::Result<()> {
+        let parse_all = |urls: &[String]| {
+            urls.iter()
+                .map(|url| Url::parse(url))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        self.current_url = Url::parse(&state.current_url)?;
+        self.history = parse_all(&state.history)?;
+        self.future = parse_all(&state.future)?;
+        self.refresh();
         Ok(())
     }
 
+    /// Write the cookies that page servers have set to disk
+    pub fn save_cookies(&self) -> anyhow::Result<()> {
+        self.cookies.save()
+    }
+
     /// Reads the current page's payload
     pub fn current(&self) -> Option<&Payload> {
         self.current_page.as_ref()
     }
 
-    /// Loads a given URL, handling it specially if it is a local file
-    async fn load_url(url: Url) -> anyhow::Result<Payload> {
+    /// Loads a given URL, handling it specially if it is not an HTTP URL
+    ///
+    /// Pages can be JSON, YAML or TOML, determined by the Content-Type or
+    /// the file extension.
+    async fn load_url(
+        client: reqwest::Client,
+        credentials: Arc<Credentials>,
+        url: Url,
+    ) -> anyhow::Result<Payload> {
+        let mut payload = if url.scheme() == "exec" {
+            Self::run_program(&url).await?
+        } else {
+            let (contents, content_type) = Self::fetch_bytes(&client, &credentials, &url).await?;
+            // The "path" of a data URL is its contents, it has no file name
+            let path = if url.scheme() == "data" {
+                ""
+            } else {
+                url.path()
+            };
+            PageFormat::detect(content_type.as_deref(), path).parse(&contents)?
+        };
+
+        Self::load_images(&client, &credentials, &local_base(&url)?, &mut payload).await;
+        Ok(payload)
+    }
+
+    /// Load the images of a page that are given by URL, and fit the ones that
+    /// are given inline
+    ///
+    /// Images that fail to load are reported and left out, rather than failing
+    /// the whole page.
+    async fn load_images(
+        client: &reqwest::Client,
+        credentials: &Credentials,
+        base: &Url,
+        payload: &mut Payload,
+    ) {
+        for image in &mut payload.images {
+            let ImageSource::Src { src } = &image.source else {
+                images::fit(image);
+                continue;
+            };
+            let src = src.clone();
+            if let Err(e) = Self::load_image(client, credentials, base, &src, image).await {
+                eprintln!("Failed to load image {}: {:#}", src, e);
+            }
+        }
+    }
+
+    async fn load_image(
+        client: &reqwest::Client,
+        credentials: &Credentials,
+        base: &Url,
+        src: &str,
+        image: &mut ImageSpec,
+    ) -> anyhow::Result<()> {
+        let url = base.join(src)?;
+        if url.scheme() == "exec" {
+            bail!("Images can't be loaded from programs: {}", url);
+        }
+        let (contents, _) = Self::fetch_bytes(client, credentials, &url).await?;
+        images::resolve(image, &contents)
+    }
+
+    /// Read the contents at the given URL, along with their content type if the
+    /// URL has one
+    async fn fetch_bytes(
+        client: &reqwest::Client,
+        credentials: &Credentials,
+        url: &Url,
+    ) -> anyhow::Result<(Vec<u8>, Option<String>)> {
         if url.scheme() == "file" {
             let Ok(file_path) = url.to_file_path() else {
                 bail!("Not a valid file path: {:?}", url);
             };
-            let mut file = File::open(&file_path)
+            let contents = fs::read(&file_path)
                 .with_context(|| format!("Failed to open {}", file_path.display()))?;
-            let mut contents = String::new();
-            file.read_to_string(&mut contents)?;
-            let payload: Payload = serde_json::from_str(&contents)?;
-            Ok(payload)
+            Ok((contents, None))
+        } else if url.scheme() == "data" {
+            let data_url = DataUrl::process(url.as_str())?;
+            let (body, _) = data_url
+                .decode_to_vec()
+                .map_err(|_| anyhow!("Invalid base64 in data URL"))?;
+            Ok((body, Some(data_url.mime_type().to_string())))
+        } else if url.scheme() == unix_socket::SCHEME {
+            // Cookies are not supported over Unix sockets, but credentials are
+            let request = Self::request(client, credentials, url)?.build()?;
+            let response = unix_socket::get(url, request.headers()).await?;
+            let content_type = Self::content_type(response.headers());
+            Ok((response.into_body().to_vec(), content_type))
         } else {
-            let response = reqwest::get(url).await?;
-            let payload: Payload = response.json().await?;
-            Ok(payload)
+            let response = Self::get(client, credentials, url).await?;
+            let content_type = Self::content_type(response.headers());
+            Ok((response.bytes().await?.to_vec(), content_type))
         }
     }
+
+    /// A GET request with the client's capabilities and the credentials for the URL
+    ///
+    /// The headers are added to every request rather than as defaults of the
+    /// client, since requests over Unix sockets don't go through the client.
+    fn request(
+        client: &reqwest::Client,
+        credentials: &Credentials,
+        url: &Url,
+    ) -> anyhow::Result<RequestBuilder> {
+        let capabilities = Capabilities::new(env!("CARGO_PKG_VERSION"));
+        let request = client.get(url.clone()).headers(capabilities.to_headers());
+        credentials.apply(url, request)
+    }
+
+    /// Send a GET request, following redirects
+    ///
+    /// Every request gets the credentials for its own URL, so secrets for one
+    /// host are not sent to the host it redirects to.
+    async fn get(
+        client: &reqwest::Client,
+        credentials: &Credentials,
+        url: &Url,
+    ) -> anyhow::Result<reqwest::Response> {
+        let mut url = url.clone();
+        for _ in 0..MAX_REDIRECTS {
+            let request = Self::request(client, credentials, &url)?;
+            let response = request.send().await?;
+            let location = response.headers().get(LOCATION);
+            let Some(location) = location.filter(|_| response.status().is_redirection()) else {
+                return Ok(response);
+            };
+            url = url.join(location.to_str()?)?;
+            if !matches!(url.scheme(), "http" | "https") {
+                bail!("Refusing to follow a redirect to {}", url);
+            }
+        }
+        bail!("Too many redirects, stopped at {}", url)
+    }
+
+    fn content_type(headers: &HeaderMap) -> Option<String> {
+        let content_type = headers.get(CONTENT_TYPE)?;
+        content_type.to_str().ok().map(str::to_string)
+    }
+
+    /// Runs the program named by an `exec:` URL, and reads the JSON payload from its
+    /// output
+    ///
+    /// Query parameters are passed to the program as `key=value` arguments, and
+    /// the program runs in its own directory.
+    async fn run_program(url: &Url) -> anyhow::Result<Payload> {
+        let program = program_path(url)?;
+        let args = url
+            .query_pairs()
+            .map(|(key, value)| format!("{}={}", key, value));
+
+        let mut command = Command::new(&program);
+        // A load that is cancelled or replaced stops its program too
+        command.args(args).kill_on_drop(true);
+        if let Some(dir) = program.parent() {
+            command.current_dir(dir);
+        }
+        let output = command
+            .output()
+            .await
+            .with_context(|| format!("Failed to run {}", program.display()))?;
+        if !output.status.success() {
+            bail!(
+                "{} failed ({}): {}",
+                program.display(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        PageFormat::Json.parse(&output.stdout)
+    }
+}
+
+/// The URL that relative URLs on a page are resolved against
+///
+/// Programs run from local files, so links on their pages are relative to that
+/// file, and don't run programs themselves.
+fn local_base(url: &Url) -> anyhow::Result<Url> {
+    match url.scheme() {
+        "exec" => Ok(Url::parse(&format!("file://{}", url.path()))?),
+        _ => Ok(url.clone()),
+    }
+}
+
+/// The file of the program that an `exec:` URL runs
+fn program_path(url: &Url) -> anyhow::Result<PathBuf> {
+    let Ok(program) = local_base(url)?.to_file_path() else {
+        bail!("Not a valid program path: {:?}", url);
+    };
+    Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// A navigator showing the given page, after the given pages
+    fn navigator_at(current: &str, history: &[&str]) -> Navigator {
+        let mut navigator =
+            Navigator::new(Credentials::default(), CookieJar::load().unwrap()).unwrap();
+        navigator.current_url = Url::parse(current).unwrap();
+        navigator.history = history.iter().map(|url| Url::parse(url).unwrap()).collect();
+        navigator
+    }
+
+    fn resolve(navigator: &Navigator, url: &str) -> anyhow::Result<String> {
+        Ok(navigator.resolve(url, false)?.to_string())
+    }
+
+    #[test]
+    fn local_pages_run_programs() {
+        let navigator = navigator_at("file:///pages/menu.json", &[]);
+        assert_eq!(
+            resolve(&navigator, "exec:scripts/status.sh?verbose=1").unwrap(),
+            "exec:///pages/scripts/status.sh?verbose=1"
+        );
+        assert_eq!(
+            resolve(&navigator, "exec:///bin/status").unwrap(),
+            "exec:///bin/status"
+        );
+
+        let navigator = navigator_at("restpad:bookmarks", &["https://example.com/"]);
+        assert!(resolve(&navigator, "exec:///bin/status").is_ok());
+    }
+
+    #[test]
+    fn links_on_program_pages_are_files() {
+        let navigator = navigator_at("exec:///pages/menu.sh", &[]);
+        assert_eq!(
+            resolve(&navigator, "other.json").unwrap(),
+            "file:///pages/other.json"
+        );
+        assert_eq!(
+            resolve(&navigator, "exec:other.sh").unwrap(),
+            "exec:///pages/other.sh"
+        );
+    }
+
+    #[test]
+    fn remote_pages_dont_run_programs() {
+        let navigator = navigator_at("https://example.com/menu", &["file:///pages/menu.json"]);
+        for url in ["exec:status.sh", "exec:///bin/status", "exec:/bin/status"] {
+            let error = resolve(&navigator, url).unwrap_err();
+            assert!(error.to_string().contains("Only local pages"), "{}", error);
+        }
+        assert_eq!(
+            resolve(&navigator, "other").unwrap(),
+            "https://example.com/other"
+        );
+    }
+
+    #[test]
+    fn the_user_runs_programs() {
+        let navigator = navigator_at("https://example.com/menu", &[]);
+        assert!(navigator.resolve("exec:///bin/status", true).is_ok());
+    }
+
+    #[test]
+    fn inline_pages_are_trusted_like_their_page() {
+        let inline = "data:application/json,%7B%7D";
+        let navigator = navigator_at(inline, &["file:///pages/menu.json"]);
+        assert_eq!(
+            resolve(&navigator, "exec:status.sh").unwrap(),
+            "exec:///pages/status.sh"
+        );
+
+        let navigator = navigator_at(inline, &["file:///pages/menu.json", "https://example.com/"]);
+        assert!(resolve(&navigator, "exec:status.sh").is_err());
+    }
+
+    #[tokio::test]
+    async fn failed_load_keeps_watching_file() {
+        let dir = env::temp_dir().join(format!("restpad-watch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let page = dir.join("page.json");
+        fs::write(&page, r#"{"buttons": []}"#).unwrap();
+
+        let mut navigator = navigator_at("file:///", &[]);
+        navigator.open(page.to_str().unwrap()).unwrap();
+        navigator.finish_load().await.unwrap();
+        navigator.navigate("http://127.0.0.1:1/").unwrap();
+        assert!(navigator.finish_load().await.is_err());
+
+        fs::write(
+            &page,
+            r##"{"buttons": [{"x": 0, "y": 0, "color": "#FFFFFF"}]}"##,
+        )
+        .unwrap();
+        let reload = tokio::time::timeout(Duration::from_secs(5), navigator.finish_load()).await;
+        fs::remove_dir_all(&dir).unwrap();
+        reload.expect("the page wasn't reloaded").unwrap();
+        assert_eq!(navigator.current().unwrap().buttons.len(), 1);
+    }
+
+    #[test]
+    fn programs_cant_be_on_other_hosts() {
+        let navigator = navigator_at("file:///pages/menu.json", &[]);
+        assert!(resolve(&navigator, "exec://example.com/bin/status").is_err());
+    }
 }
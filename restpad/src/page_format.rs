@@ -0,0 +1,200 @@
+use std::{fmt, str};
+
+use serde_yaml::with::singleton_map_recursive;
+
+use crate::payload::Payload;
+
+/// The formats that pages can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PageFormat {
+    #[default]
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl PageFormat {
+    /// Determine the format of a page from its Content-Type, falling back to its
+    /// file extension and then to JSON
+    pub fn detect(content_type: Option<&str>, path: &str) -> PageFormat {
+        content_type
+            .and_then(PageFormat::from_content_type)
+            .or_else(|| PageFormat::from_path(path))
+            .unwrap_or_default()
+    }
+
+    /// Determine the format from a Content-Type (or any MIME type)
+    ///
+    /// Parameters like `; charset=utf-8` are ignored. Returns `None` for types that
+    /// don't say anything about the format, like `text/plain`.
+    pub fn from_content_type(content_type: &str) -> Option<PageFormat> {
+        let essence = content_type.split(';').next()?.trim().to_ascii_lowercase();
+        match essence.as_str() {
+            "application/json" | "text/json" => Some(PageFormat::Json),
+            "application/yaml" | "application/x-yaml" | "text/yaml" | "text/x-yaml" => {
+                Some(PageFormat::Yaml)
+            }
+            "application/toml" | "text/toml" | "text/x-toml" => Some(PageFormat::Toml),
+            _ if essence.ends_with("+json") => Some(PageFormat::Json),
+            _ if essence.ends_with("+yaml") => Some(PageFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Determine the format from the extension of a file name or URL path
+    pub fn from_path(path: &str) -> Option<PageFormat> {
+        let (_, extension) = path.rsplit_once('.')?;
+        match extension.to_ascii_lowercase().as_str() {
+            "json" => Some(PageFormat::Json),
+            "yaml" | "yml" => Some(PageFormat::Yaml),
+            "toml" => Some(PageFormat::Toml),
+            _ => None,
+        }
+    }
+
+    /// Parse a page in this format
+    ///
+    /// If the page is invalid, the error is a `ParseError`.
+    pub fn parse(self, contents: &[u8]) -> anyhow::Result<Payload> {
+        let result = match self {
+            PageFormat::Json => serde_json::from_slice(contents).map_err(|e| {
+                ParseError::new(
+                    e.to_string(),
+                    Some((e.line(), e.column())).filter(|l| l.0 > 0),
+                )
+            }),
+            // Enums are written as maps like in JSON (`flash: "#0f0"`), rather
+            // than with YAML tags (`!flash "#0f0"`)
+            PageFormat::Yaml => {
+                singleton_map_recursive::deserialize(serde_yaml::Deserializer::from_slice(contents))
+                    .map_err(|e| {
+                        ParseError::new(e.to_string(), e.location().map(|l| (l.line(), l.column())))
+                    })
+            }
+            PageFormat::Toml => toml::from_str(str::from_utf8(contents)?).map_err(|e| {
+                let location = e.span().map(|span| line_and_column(contents, span.start));
+                ParseError::new(e.message().to_string(), location)
+            }),
+        };
+        Ok(result?)
+    }
+}
+
+/// A page that could not be parsed
+#[derive(Debug)]
+pub struct ParseError {
+    /// What is wrong with the page
+    pub message: String,
+
+    /// Line and column of the problem, both starting at 1, if known
+    pub location: Option<(usize, usize)>,
+}
+
+impl ParseError {
+    fn new(message: String, location: Option<(usize, usize)>) -> Self {
+        // Some parsers already put the location in the message
+        let message = match location {
+            Some((line, column)) => message
+                .strip_suffix(&format!(" at line {} column {}", line, column))
+                .map(str::to_string)
+                .unwrap_or(message),
+            None => message,
+        };
+        ParseError { message, location }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location {
+            Some((line, column)) => {
+                write!(f, "{} at line {} column {}", self.message, line, column)
+            }
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Convert a byte offset into a line and column, both starting at 1
+fn line_and_column(contents: &[u8], offset: usize) -> (usize, usize) {
+    let before = &contents[..offset.min(contents.len())];
+    let line_start = before
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+    let column = String::from_utf8_lossy(&before[line_start..])
+        .chars()
+        .count()
+        + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use restpad_sdk::payload::{Action, Style, Target};
+
+    use super::*;
+
+    #[test]
+    fn detects_format() {
+        let detect = PageFormat::detect;
+        assert_eq!(
+            detect(Some("application/yaml"), "page.json"),
+            PageFormat::Yaml
+        );
+        assert_eq!(
+            detect(Some("text/toml; charset=utf-8"), ""),
+            PageFormat::Toml
+        );
+        assert_eq!(
+            detect(Some("application/vnd.page+json"), ""),
+            PageFormat::Json
+        );
+        assert_eq!(
+            detect(Some("text/plain"), "/pages/menu.YML"),
+            PageFormat::Yaml
+        );
+        assert_eq!(detect(None, "menu.toml"), PageFormat::Toml);
+        assert_eq!(detect(None, "/cgi-bin/menu"), PageFormat::Json);
+    }
+
+    #[test]
+    fn parses_yaml_enums_as_maps() {
+        let yaml = b"buttons:
+  - x: 0
+    y: 0
+    color: \"#FF0000\"
+    style:
+      flash: \"#00FF00\"
+    onPress:
+      navigate:
+        href: next.yaml
+";
+        let payload = PageFormat::Yaml.parse(yaml).unwrap();
+        let button = &payload.buttons[0];
+        assert!(matches!(button.style, Style::Flash(Some(_))));
+        assert!(matches!(
+            &button.on_press,
+            Some(Action::Navigate(Target::Href { href })) if href == "next.yaml"
+        ));
+    }
+
+    #[test]
+    fn reports_location() {
+        let error = PageFormat::Json
+            .parse(b"{\n  \"buttons\": 1\n}")
+            .unwrap_err();
+        let error = error.downcast::<ParseError>().unwrap();
+        assert_eq!(error.location, Some((2, 14)));
+        assert!(!error.message.contains("line"), "{}", error.message);
+
+        let error = PageFormat::Toml
+            .parse(b"\n[[buttons]]\nx = \"a\"\n")
+            .unwrap_err();
+        let error = error.downcast::<ParseError>().unwrap();
+        assert_eq!(error.location.map(|(line, _)| line), Some(3));
+    }
+}
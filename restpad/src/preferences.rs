@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::navigator::NavigationState;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Preferences {
     /// Brightness on a scale from 0 to 8
@@ -11,3 +13,23 @@ impl Default for Preferences {
         Self { brightness: 8 }
     }
 }
+
+/// The pages the user has bookmarked
+///
+/// Kept apart from the preferences, so that the preferences file stays
+/// readable by older versions.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Bookmarks {
+    /// Bookmarked page URLs, in the order they were added
+    pub urls: Vec<String>,
+}
+
+/// Where the user was when restpad last exited
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Session {
+    /// The page that was open, and its history
+    pub navigation: Option<NavigationState>,
+
+    /// Vertical scroll position on that page
+    pub y_scroll: u32,
+}
@@ -0,0 +1,952 @@
+use std::{collections::HashSet, fs::File, io::BufWriter, ops::Range, path::Path, time::Duration};
+
+use anyhow::{bail, Context};
+use chrono::{DateTime, Utc};
+use cond::cond;
+use hex_color::HexColor;
+
+use crate::{
+    any_launchpad::{
+        palette_to_rgb, rgb_to_palette, Button, ButtonStyle, Buttons, PaletteColor, RgbColor,
+    },
+    clock::{clock_text, countdown_text},
+    cookies::CookieJar,
+    credentials::Credentials,
+    embedded_gfx::{draw_image, draw_text, Typeface},
+    navigator::Navigator,
+    payload::{
+        Align, Animation, ButtonSpec, ChartSpec, Direction, Easing, Fit, ImageSource, ImageSpec,
+        Orientation, Payload, ProgressSpec, Scroll, Style, TextSpec,
+    },
+    preferences::Preferences,
+    GRID_HEIGHT, GRID_WIDTH,
+};
+
+/// How long each animation frame is shown
+pub static FRAME_DURATION: Duration = Duration::from_millis(100);
+
+/// Size of a pad in a rendered image, in pixels, including the gap around it
+static PAD_SIZE: u32 = 32;
+
+/// Gap between pads in a rendered image, in pixels
+static PAD_GAP: u32 = 4;
+
+/// Color of the space between pads
+static BACKGROUND: RgbColor = RgbColor {
+    r: 0x20,
+    g: 0x20,
+    b: 0x20,
+};
+
+/// Load a page and render what the pad would show, without a device
+///
+/// Writes a PNG image to `output` if given, and prints the pads as truecolor
+/// ANSI blocks otherwise.
+pub async fn run(
+    url: &str,
+    credentials: Credentials,
+    y_scroll: u32,
+    frame: i32,
+    output: Option<&Path>,
+) -> anyhow::Result<()> {
+    let mut navigator = Navigator::new(credentials, CookieJar::load()?)?;
+    navigator.open(url)?;
+    navigator.finish_load().await?;
+    let Some(payload) = navigator.current() else {
+        bail!("{}: page did not load", url);
+    };
+
+    let pressed_buttons = HashSet::new();
+    let mut state = PadState {
+        page: Some(payload),
+        pressed_buttons: &pressed_buttons,
+        y_scroll: 0,
+        frame,
+        now: Utc::now(),
+        brightness: Preferences::default().brightness,
+        has_history: false,
+        has_future: false,
+        is_loading: false,
+        load_failed: false,
+        is_bookmarked: false,
+    };
+    state.y_scroll = y_scroll.min(state.scrollable_y_height());
+
+    let pixels = render_pads(&state.calculate_buttons());
+    match output {
+        Some(path) => {
+            write_png(&pixels, path).with_context(|| format!("Failed to write {}", path.display()))
+        }
+        None => {
+            print!("{}", to_ansi(&pixels));
+            Ok(())
+        }
+    }
+}
+
+/// Everything that determines what the pads show
+pub struct PadState<'a> {
+    /// The page being shown, if any
+    pub page: Option<&'a Payload>,
+
+    /// Buttons that are currently held down
+    pub pressed_buttons: &'a HashSet<Button>,
+
+    /// How many rows the page is scrolled down
+    pub y_scroll: u32,
+
+    /// Animation frame, counting up from 0 when the page loads
+    pub frame: i32,
+
+    /// The current time, for clocks and countdowns
+    pub now: DateTime<Utc>,
+
+    /// Brightness on a scale from 0 to 8
+    pub brightness: u8,
+
+    pub has_history: bool,
+    pub has_future: bool,
+    pub is_loading: bool,
+    pub load_failed: bool,
+    pub is_bookmarked: bool,
+}
+
+impl PadState<'_> {
+    /// Calculate the style of every pad
+    pub fn calculate_buttons(&self) -> Buttons {
+        let mut buttons = Buttons::new();
+
+        // UP and DOWN buttons control brightness
+        buttons.insert(
+            Button::UP,
+            cond! {
+                self.pressed_buttons.contains(&Button::UP) => PaletteColor::RED,
+                self.brightness < 8 => PaletteColor::WHITE,
+                _ => PaletteColor::BLACK
+            }
+            .into(),
+        );
+        buttons.insert(
+            Button::DOWN,
+            cond! {
+                self.pressed_buttons.contains(&Button::DOWN) => PaletteColor::RED,
+                self.brightness > 0 => PaletteColor::WHITE,
+                _ => PaletteColor::BLACK
+            }
+            .into(),
+        );
+        // LEFT and RIGHT are for the browser history
+        buttons.insert(
+            Button::LEFT,
+            cond! {
+                self.pressed_buttons.contains(&Button::LEFT) => PaletteColor::YELLOW,
+                self.has_history => PaletteColor::WHITE,
+                _ => PaletteColor::BLACK
+            }
+            .into(),
+        );
+        buttons.insert(
+            Button::RIGHT,
+            cond! {
+                self.pressed_buttons.contains(&Button::RIGHT) => PaletteColor::YELLOW,
+                self.has_future => PaletteColor::WHITE,
+                _ => PaletteColor::BLACK
+            }
+            .into(),
+        );
+
+        // The "refresh" button is always on, lights up while a page is loading,
+        // and turns red if the page failed to load
+        buttons.insert(
+            Button::MIXER,
+            cond! {
+            self.pressed_buttons.contains(&Button::MIXER) => PaletteColor::YELLOW,
+            self.is_loading => PaletteColor::YELLOW,
+            self.load_failed => PaletteColor::RED,
+            _ => PaletteColor::WHITE }
+            .into(),
+        );
+
+        // SESSION shows the bookmarks, and is green if the current page is one of them
+        buttons.insert(
+            Button::SESSION,
+            cond! {
+                self.pressed_buttons.contains(&Button::SESSION) => PaletteColor::YELLOW,
+                self.is_bookmarked => PaletteColor::GREEN,
+                _ => PaletteColor::WHITE
+            }
+            .into(),
+        );
+
+        // The right-hand column is for vertical scroll
+        let invis_height = self.scrollable_y_height();
+        let scroll_range = (GRID_HEIGHT - 2 - 1) as u64;
+        if let Some(scroll_pos) =
+            (self.y_scroll as u64 * scroll_range).checked_div(invis_height as u64)
+        {
+            let up_button = Button::GridButton {
+                x: GRID_WIDTH as u8,
+                y: 0,
+            };
+            let down_button = Button::GridButton {
+                x: GRID_WIDTH as u8,
+                y: GRID_HEIGHT as u8 - 1,
+            };
+            buttons.insert(
+                up_button,
+                cond! {
+                    self.pressed_buttons.contains(&up_button) => PaletteColor::YELLOW,
+                    _ => PaletteColor::WHITE
+                }
+                .into(),
+            );
+            buttons.insert(
+                down_button,
+                cond! {
+                    self.pressed_buttons.contains(&down_button) => PaletteColor::YELLOW,
+                    _ => PaletteColor::WHITE
+                }
+                .into(),
+            );
+
+            // Indicate the scroll position
+            buttons.insert(
+                Button::GridButton {
+                    x: GRID_WIDTH as u8,
+                    y: 1 + scroll_pos as u8,
+                },
+                PaletteColor::DARK_GRAY.into(),
+            );
+        }
+
+        let Some(payload) = self.page else {
+            return buttons;
+        };
+
+        for image in &payload.images {
+            // Images that are still loading aren't shown
+            let Some(pixels) = current_frame(image, self.frame) else {
+                continue;
+            };
+            if image.x >= GRID_WIDTH {
+                continue;
+            }
+
+            // Only the part of the image that is on the grid is fitted
+            let (width, height) = image_size(image, pixels);
+            let (_, visible_rows) = self.visible_pads();
+            let columns = 0..width.min(GRID_WIDTH - image.x);
+            let rows = visible_rows.start.saturating_sub(image.y).min(height)
+                ..visible_rows.end.saturating_sub(image.y).min(height);
+            let pixels = fit_area(pixels, (width, height), image.fit, &columns, &rows)
+                .iter()
+                .map(|row| row.iter().map(|p| p.map(hex_to_rgb)).collect())
+                .collect::<Vec<_>>();
+            let pos = (
+                image.x as i32,
+                self.grid_y(image.y.saturating_add(rows.start)),
+            );
+            let size = (columns.end, rows.end - rows.start);
+            draw_image(&mut buttons, &pixels, pos, size);
+        }
+
+        for progress in &payload.progress {
+            for (pad, color) in self.pads_from_progress(progress) {
+                buttons.insert(pad, ButtonStyle::Rgb(color));
+            }
+        }
+
+        for chart in &payload.charts {
+            for (pad, color) in self.pads_from_chart(chart) {
+                buttons.insert(pad, ButtonStyle::Rgb(color));
+            }
+        }
+
+        for button in &payload.buttons {
+            // This adjusts the button in virtual-space to the pad in real-space,
+            // leaving the right-hand column to the scroll bar
+            let pads = self.pads_from_buttonspec(button);
+
+            let is_pressed = pads.iter().any(|p| self.pressed_buttons.contains(p));
+            let press_color = button.press_color.or(payload.default_press_color);
+
+            let animated = button
+                .animation
+                .as_ref()
+                .and_then(|animation| animation_color(animation, self.frame));
+            let style = match (press_color, animated) {
+                (Some(press_color), _) if is_pressed => ButtonStyle::Rgb(hex_to_rgb(press_color)),
+                (_, Some(color)) => ButtonStyle::Rgb(color),
+                _ => parse_button_style(button),
+            };
+
+            for pad in pads {
+                buttons.insert(pad, style);
+            }
+        }
+
+        for text in payload.text.iter().chain(&self.widget_texts()) {
+            let color = hex_to_rgb(text.color);
+            let pos = (text.x as i32, self.grid_y(text.y));
+            let size = text_size(text);
+            let font = text.font;
+            let typeface = Typeface {
+                font,
+                glyphs: &payload.glyphs,
+            };
+
+            let (lines, length, visible) = match text.direction {
+                Direction::Horizontal => {
+                    let lines = vec![text.text.clone()];
+                    (lines, typeface.text_width(&text.text), size.0)
+                }
+                Direction::Vertical => {
+                    let lines = wrap_text(&text.text, typeface, size.0);
+                    let length = lines.len() as u32 * font.height();
+                    (lines, length, size.1)
+                }
+            };
+
+            for shift in marquee(text, self.frame, length, visible) {
+                for (i, line) in lines.iter().enumerate() {
+                    let x = align_x(text.align, typeface.text_width(line), size.0);
+                    let y = i as i32 * font.height() as i32;
+                    let offset = match text.direction {
+                        Direction::Horizontal => (x + shift, y),
+                        Direction::Vertical => (x, y + shift),
+                    };
+                    draw_text(&mut buttons, line, pos, size, offset, color, typeface);
+                }
+            }
+        }
+        buttons
+    }
+
+    fn y_max(&self) -> u32 {
+        let Some(payload) = self.page else {
+            return 0;
+        };
+
+        let y_max_buttons = payload
+            .buttons
+            .iter()
+            .map(|b| b.y.saturating_add(button_size(b).1) - 1)
+            .max()
+            .unwrap_or(0);
+        let y_max_text = payload
+            .text
+            .iter()
+            .chain(&self.widget_texts())
+            .map(|t| t.y.saturating_add(text_size(t).1))
+            .max()
+            .unwrap_or(0);
+        let y_max_images = payload
+            .images
+            .iter()
+            .filter_map(|image| {
+                let pixels = current_frame(image, 0).unwrap_or_default();
+                let (_, height) = image_size(image, pixels);
+                image.y.saturating_add(height).checked_sub(1)
+            })
+            .max()
+            .unwrap_or(0);
+        let y_max_progress = payload
+            .progress
+            .iter()
+            .filter_map(|p| p.y.saturating_add(progress_size(p).1).checked_sub(1))
+            .max()
+            .unwrap_or(0);
+        let y_max_charts = payload
+            .charts
+            .iter()
+            .filter_map(|c| c.y.saturating_add(chart_size(c).1).checked_sub(1))
+            .max()
+            .unwrap_or(0);
+        y_max_buttons
+            .max(y_max_text)
+            .max(y_max_images)
+            .max(y_max_progress)
+            .max(y_max_charts)
+    }
+
+    /// How many rows of the page don't fit on the pad
+    pub fn scrollable_y_height(&self) -> u32 {
+        self.y_max().saturating_add(1).saturating_sub(GRID_HEIGHT)
+    }
+
+    /// Find the button on the page that is shown on the given pad
+    pub fn find_button(&self, pad: Button) -> Option<ButtonSpec> {
+        let payload = self.page?;
+        for button in &payload.buttons {
+            let pads = self.pads_from_buttonspec(button);
+            if pads.contains(&pad) {
+                return Some(button.clone());
+            }
+        }
+        None
+    }
+
+    /// The pads that show the given button, leaving out the parts that are
+    /// scrolled off the grid or run past its right edge
+    fn pads_from_buttonspec(&self, button: &ButtonSpec) -> Vec<Button> {
+        let (width, height) = button_size(button);
+        (0..height)
+            .flat_map(|dy| (0..width).map(move |dx| (dx, dy)))
+            .filter_map(|(dx, dy)| {
+                self.grid_pad(button.x.saturating_add(dx), button.y.saturating_add(dy))
+            })
+            .collect()
+    }
+
+    /// The texts that the clocks and countdowns show right now
+    fn widget_texts(&self) -> Vec<TextSpec> {
+        let Some(payload) = self.page else {
+            return vec![];
+        };
+        let clocks = payload.clocks.iter().map(|c| clock_text(c, self.now));
+        let countdowns = payload
+            .countdowns
+            .iter()
+            .map(|c| countdown_text(c, self.now));
+        clocks.chain(countdowns).collect()
+    }
+
+    /// The lit pads of a progress bar that are on the grid, with their colors
+    ///
+    /// Only the pads that are on the grid are looked at, so a long bar costs
+    /// no more than a short one.
+    fn pads_from_progress(&self, progress: &ProgressSpec) -> Vec<(Button, RgbColor)> {
+        let length = progress.length;
+        let (columns, rows) = self.visible_pads();
+        let pads: Vec<(u32, u32, u32)> = match progress.orientation {
+            Orientation::Horizontal if rows.contains(&progress.y) => columns
+                .filter_map(|x| {
+                    let i = x.checked_sub(progress.x).filter(|&i| i < length)?;
+                    Some((x, progress.y, i))
+                })
+                .collect(),
+            Orientation::Vertical if columns.contains(&progress.x) => rows
+                .filter_map(|y| {
+                    let offset = y.checked_sub(progress.y).filter(|&o| o < length)?;
+                    Some((progress.x, y, length - 1 - offset))
+                })
+                .collect(),
+            _ => vec![],
+        };
+        pads.into_iter()
+            .filter_map(|(x, y, i)| Some((self.grid_pad(x, y)?, progress_color(progress, i)?)))
+            .collect()
+    }
+
+    /// The lit pads of a bar chart that are on the grid, with their colors
+    ///
+    /// Like progress bars, only the rows that are on the grid are looked at.
+    fn pads_from_chart(&self, chart: &ChartSpec) -> Vec<(Button, RgbColor)> {
+        let (width, height) = chart_size(chart);
+        let values = &chart.values[chart.values.len().saturating_sub(width as usize)..];
+        let (min, max) = chart_range(chart);
+        let (columns, rows) = self.visible_pads();
+
+        let mut pads = vec![];
+        for (column, &value) in values.iter().enumerate() {
+            let level = cond! {
+                max > min => ((value - min) / (max - min)).clamp(0.0, 1.0),
+                _ => 0.0,
+            };
+            let color = chart
+                .thresholds
+                .iter()
+                .filter(|threshold| value >= threshold.value)
+                .max_by(|a, b| a.value.total_cmp(&b.value))
+                .map_or(chart.color, |threshold| threshold.color);
+
+            let x = chart.x.saturating_add(column as u32);
+            if !columns.contains(&x) {
+                continue;
+            }
+            for y in rows.clone() {
+                let Some(offset) = y.checked_sub(chart.y).filter(|&o| o < height) else {
+                    continue;
+                };
+                let i = height - 1 - offset;
+                let fill = fill_color(hex_to_rgb(color), level * height as f64, i);
+                if let (Some(pad), Some(color)) = (
+                    self.grid_pad(x, y),
+                    fill.or(chart.background.map(hex_to_rgb)),
+                ) {
+                    pads.push((pad, color));
+                }
+            }
+        }
+        pads
+    }
+
+    /// The columns and rows of the page that are on the grid
+    fn visible_pads(&self) -> (Range<u32>, Range<u32>) {
+        (
+            0..GRID_WIDTH,
+            self.y_scroll..self.y_scroll.saturating_add(GRID_HEIGHT),
+        )
+    }
+
+    /// The row on the grid of a row of the page, which is negative above the
+    /// grid
+    fn grid_y(&self, y: u32) -> i32 {
+        (i64::from(y) - i64::from(self.y_scroll)).clamp(i32::MIN.into(), i32::MAX.into()) as i32
+    }
+
+    /// The pad on the device for a pad of the page, if it isn't scrolled out of
+    /// view or in the scroll column
+    fn grid_pad(&self, x: u32, y: u32) -> Option<Button> {
+        let y = y.checked_sub(self.y_scroll)?;
+        (x < GRID_WIDTH && y < GRID_HEIGHT).then(|| Button::grid(x as u8, y as u8))
+    }
+}
+
+/// The width and height of a button, in pads
+pub fn button_size(button: &ButtonSpec) -> (u32, u32) {
+    (
+        button.width.unwrap_or(1).max(1) as u32,
+        button.height.unwrap_or(1).max(1) as u32,
+    )
+}
+
+/// The width and height of a progress bar, in pads
+pub fn progress_size(progress: &ProgressSpec) -> (u32, u32) {
+    match progress.orientation {
+        Orientation::Horizontal => (progress.length, 1),
+        Orientation::Vertical => (1, progress.length),
+    }
+}
+
+/// The color of the `i`th pad of a progress bar, counted from its empty end
+///
+/// The last filled pad is dimmed by how far it is filled. Pads that aren't
+/// filled have the background color, if there is one.
+fn progress_color(progress: &ProgressSpec, i: u32) -> Option<RgbColor> {
+    let length = progress.length;
+    let filled = if progress.max > 0.0 {
+        (progress.value / progress.max).clamp(0.0, 1.0) * length as f64
+    } else {
+        0.0
+    };
+
+    let position = i as f64 / length.saturating_sub(1).max(1) as f64;
+    let color = gradient(&progress.colors, position);
+    fill_color(color, filled, i).or(progress.background.map(hex_to_rgb))
+}
+
+/// The color of the `i`th pad of a bar that is filled up to `filled` pads, or
+/// `None` if it isn't filled at all
+///
+/// The last filled pad is dimmed by how far it is filled.
+fn fill_color(color: RgbColor, filled: f64, i: u32) -> Option<RgbColor> {
+    let fraction = (filled - i as f64).clamp(0.0, 1.0);
+    (fraction > 0.0).then(|| mix(hex_to_rgb(HexColor::BLACK), color, fraction))
+}
+
+/// The width and height of a bar chart, in pads
+pub fn chart_size(chart: &ChartSpec) -> (u32, u32) {
+    let width = chart
+        .width
+        .unwrap_or((chart.values.len() as u32).min(GRID_WIDTH.saturating_sub(chart.x)));
+    (width, chart.height)
+}
+
+/// The values at the bottom and the top of a bar chart
+fn chart_range(chart: &ChartSpec) -> (f64, f64) {
+    let lowest = chart.values.iter().copied().fold(f64::INFINITY, f64::min);
+    let highest = chart
+        .values
+        .iter()
+        .copied()
+        .fold(f64::NEG_INFINITY, f64::max);
+    let min = chart.min.unwrap_or(lowest.min(0.0));
+    let max = chart.max.unwrap_or(highest.max(min));
+    (min, max)
+}
+
+/// The color at the given position between 0 and 1 of colors that are spread
+/// out evenly; no colors at all are white
+fn gradient(colors: &[HexColor], position: f64) -> RgbColor {
+    let Some(last) = colors.len().checked_sub(1) else {
+        return hex_to_rgb(HexColor::WHITE);
+    };
+    let position = position.clamp(0.0, 1.0) * last as f64;
+    let index = (position as usize).min(last.saturating_sub(1));
+    let next = (index + 1).min(last);
+    mix(
+        hex_to_rgb(colors[index]),
+        hex_to_rgb(colors[next]),
+        position - index as f64,
+    )
+}
+
+/// The color of an animated button at the given frame
+///
+/// Returns `None` for an animation without keyframes.
+fn animation_color(animation: &Animation, frame: i32) -> Option<RgbColor> {
+    let keyframes = &animation.keyframes;
+    let first = keyframes.first()?;
+    let last = keyframes.last()?;
+    let total: u64 = keyframes.iter().map(|k| k.duration_ms as u64).sum();
+    let elapsed = frame.max(0) as u64 * FRAME_DURATION.as_millis() as u64;
+    let mut time = elapsed + animation.offset_ms as u64;
+    if total == 0 {
+        return Some(hex_to_rgb(first.color));
+    } else if animation.looping {
+        time %= total;
+    } else if time >= total {
+        return Some(hex_to_rgb(last.color));
+    }
+
+    for (i, keyframe) in keyframes.iter().enumerate() {
+        let duration = keyframe.duration_ms as u64;
+        if time >= duration {
+            time -= duration;
+            continue;
+        }
+        // The last keyframe fades back into the first one, or stays if the
+        // animation only runs once
+        let next = match keyframes.get(i + 1) {
+            Some(next) => next,
+            None if animation.looping => first,
+            None => keyframe,
+        };
+        let progress = time as f64 / duration as f64;
+        let amount = match animation.easing {
+            Easing::Linear => progress,
+            Easing::EaseInOut => progress * progress * (3.0 - 2.0 * progress),
+            Easing::Step => 0.0,
+        };
+        return Some(mix(
+            hex_to_rgb(keyframe.color),
+            hex_to_rgb(next.color),
+            amount,
+        ));
+    }
+    Some(hex_to_rgb(last.color))
+}
+
+/// Blend two colors, from all `from` at 0 to all `to` at 1
+fn mix(from: RgbColor, to: RgbColor, amount: f64) -> RgbColor {
+    let channel =
+        |from: u8, to: u8| (from as f64 + (to as f64 - from as f64) * amount).round() as u8;
+    RgbColor {
+        r: channel(from.r, to.r),
+        g: channel(from.g, to.g),
+        b: channel(from.b, to.b),
+    }
+}
+
+/// The width and height of a text, in pads
+pub fn text_size(text: &TextSpec) -> (u32, u32) {
+    let width = text
+        .width
+        .unwrap_or(GRID_WIDTH)
+        .min(GRID_WIDTH.saturating_sub(text.x));
+    let height = match text.direction {
+        Direction::Horizontal => text.font.height(),
+        Direction::Vertical => text.height.unwrap_or(text.font.height()),
+    };
+    (width, height)
+}
+
+/// Where a line of text of the given width starts in a box of `visible` pads
+fn align_x(align: Align, width: u32, visible: u32) -> i32 {
+    let space = visible.saturating_sub(width) as i32;
+    match align {
+        Align::Left => 0,
+        Align::Center => space / 2,
+        Align::Right => space,
+    }
+}
+
+/// The offsets at which to draw a text of `length` pads in a box of `visible`
+/// pads at the given animation frame
+///
+/// Looping texts are drawn twice, so the start follows the end into view.
+fn marquee(text: &TextSpec, frame: i32, length: u32, visible: u32) -> Vec<i32> {
+    if length <= visible {
+        return vec![0];
+    }
+
+    let frame_ms = FRAME_DURATION.as_millis() as u32;
+    let frames_per_second = 1000 / frame_ms;
+    let speed = text.speed.unwrap_or(frames_per_second).max(1);
+    let pause = text.pause_ms.unwrap_or(1000) / frame_ms;
+    let frames_to_move = |distance: u32| (distance * frames_per_second).div_ceil(speed);
+    let moved = |frame: u32| frame.saturating_sub(pause) * speed / frames_per_second;
+
+    let frame = frame.max(0) as u32;
+    match text.scroll {
+        Scroll::None => vec![0],
+        Scroll::Bounce => {
+            let distance = length - visible;
+            let leg = pause + frames_to_move(distance);
+            let offset = moved(frame % leg).min(distance);
+            // Every other leg goes backwards
+            let offset = if (frame / leg) % 2 == 1 {
+                distance - offset
+            } else {
+                offset
+            };
+            vec![-(offset as i32)]
+        }
+        Scroll::Loop => {
+            let distance = length + visible;
+            let cycle = pause + frames_to_move(distance);
+            let offset = moved(frame % cycle).min(distance) as i32;
+            vec![-offset, distance as i32 - offset]
+        }
+    }
+}
+
+/// Break a text into lines that fit in the given number of pads, between words
+/// where possible
+fn wrap_text(text: &str, typeface: Typeface, width: u32) -> Vec<String> {
+    let mut lines = vec![];
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() {
+                let joined = format!("{} {}", line, word);
+                if typeface.text_width(&joined) <= width {
+                    line = joined;
+                    continue;
+                }
+                lines.push(std::mem::take(&mut line));
+            }
+
+            // The word starts a new line, and is broken up if it doesn't fit
+            for c in word.chars() {
+                line.push(c);
+                if typeface.text_width(&line) > width && line.chars().count() > 1 {
+                    line.pop();
+                    lines.push(std::mem::take(&mut line));
+                    line.push(c);
+                }
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// The pixels of an image that are shown at the given animation frame
+fn current_frame(image: &ImageSpec, frame: i32) -> Option<&[Vec<Option<HexColor>>]> {
+    match &image.source {
+        ImageSource::Pixels { pixels } => Some(pixels),
+        ImageSource::Frames { frames } => {
+            // Frames can't be shown for less than one animation frame
+            let min_delay = FRAME_DURATION.as_millis() as u32;
+            let total = frames
+                .iter()
+                .map(|f| f.delay_ms.max(min_delay))
+                .sum::<u32>();
+            let mut time = (frame.max(0) as u32 * min_delay).checked_rem(total)?;
+            frames
+                .iter()
+                .find(|f| {
+                    let delay = f.delay_ms.max(min_delay);
+                    let shown = time < delay;
+                    time = time.saturating_sub(delay);
+                    shown
+                })
+                .map(|f| f.pixels.as_slice())
+        }
+        ImageSource::Src { .. } => None,
+    }
+}
+
+/// The largest width and height of an image on the page, in pads
+///
+/// Images are fitted to their size when the page loads, so a page can't make
+/// us allocate more than this for every frame of an image.
+pub static MAX_IMAGE_PADS: u32 = 256;
+
+/// The size of an image on the grid, in pads
+///
+/// Sizes that the image doesn't set come from its pixels, as far as they fit
+/// on the grid. Neither is more than `MAX_IMAGE_PADS`.
+pub fn image_size(image: &ImageSpec, pixels: &[Vec<Option<HexColor>>]) -> (u32, u32) {
+    let pixel_width = pixels.iter().map(Vec::len).max().unwrap_or(0) as u32;
+    let pixel_height = pixels.len() as u32;
+    (
+        image
+            .width
+            .unwrap_or(pixel_width.min(GRID_WIDTH.saturating_sub(image.x)))
+            .min(MAX_IMAGE_PADS),
+        image
+            .height
+            .unwrap_or(pixel_height.min(GRID_HEIGHT))
+            .min(MAX_IMAGE_PADS),
+    )
+}
+
+/// Scale or crop rows of pixels to the given size
+pub fn fit_pixels(
+    pixels: &[Vec<Option<HexColor>>],
+    width: u32,
+    height: u32,
+    fit: Fit,
+) -> Vec<Vec<Option<HexColor>>> {
+    fit_area(pixels, (width, height), fit, &(0..width), &(0..height))
+}
+
+/// Scale or crop rows of pixels to the given size, but only work out the pads
+/// in the given columns and rows
+///
+/// When scaling down, every pad gets the average color of the pixels it covers,
+/// and is transparent if most of them are.
+fn fit_area(
+    pixels: &[Vec<Option<HexColor>>],
+    (width, height): (u32, u32),
+    fit: Fit,
+    columns: &Range<u32>,
+    rows: &Range<u32>,
+) -> Vec<Vec<Option<HexColor>>> {
+    let pixel = |x: usize, y: usize| pixels.get(y).and_then(|row| row.get(x).copied().flatten());
+    if fit == Fit::Crop {
+        return rows
+            .clone()
+            .map(|y| {
+                columns
+                    .clone()
+                    .map(|x| pixel(x as usize, y as usize))
+                    .collect()
+            })
+            .collect();
+    }
+
+    let pixel_width = pixels.iter().map(Vec::len).max().unwrap_or(0);
+    let pixel_height = pixels.len();
+    // The range of pixels that covers the given pad
+    let span = |i: u32, pads: u32, pixels: usize| {
+        let start = i as usize * pixels / pads as usize;
+        let end = ((i + 1) as usize * pixels / pads as usize).max(start + 1);
+        start..end.min(pixels)
+    };
+
+    rows.clone()
+        .map(|y| {
+            columns
+                .clone()
+                .map(|x| {
+                    let covered = span(y, height, pixel_height)
+                        .flat_map(|py| span(x, width, pixel_width).map(move |px| (px, py)))
+                        .map(|(px, py)| pixel(px, py))
+                        .collect::<Vec<_>>();
+                    let opaque = covered.iter().flatten().collect::<Vec<_>>();
+                    if opaque.is_empty() || opaque.len() * 2 < covered.len() {
+                        return None;
+                    }
+                    let average = |c: fn(&HexColor) -> u8| {
+                        (opaque.iter().map(|p| c(p) as usize).sum::<usize>() / opaque.len()) as u8
+                    };
+                    Some(HexColor::rgb(
+                        average(|p| p.r),
+                        average(|p| p.g),
+                        average(|p| p.b),
+                    ))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn parse_button_style(b: &ButtonSpec) -> ButtonStyle {
+    match b.style {
+        Style::Plain => ButtonStyle::Rgb(hex_to_rgb(b.color)),
+        Style::Pulse => ButtonStyle::Pulse(PaletteColor(rgb_to_palette(hex_to_rgb(b.color)))),
+        Style::Flash(color2) => ButtonStyle::Flash(
+            PaletteColor(rgb_to_palette(hex_to_rgb(b.color))),
+            PaletteColor(rgb_to_palette(hex_to_rgb(color2.unwrap_or_default()))),
+        ),
+    }
+}
+
+fn hex_to_rgb(color: HexColor) -> RgbColor {
+    RgbColor {
+        r: color.r,
+        g: color.g,
+        b: color.b,
+    }
+}
+
+/// The pads the way they are laid out on the device: the control buttons in a
+/// row on top, and the grid with the scroll column below
+///
+/// Returns 9 rows of 9 pads; `None` where there is no pad.
+pub fn pad_layout() -> Vec<Vec<Option<Button>>> {
+    let mut control_row = (0..GRID_WIDTH as u8)
+        .map(|index| Some(Button::ControlButton { index }))
+        .collect::<Vec<_>>();
+    control_row.push(None);
+
+    let mut rows = vec![control_row];
+    for y in 0..GRID_HEIGHT as u8 {
+        rows.push(
+            (0..=GRID_WIDTH as u8)
+                .map(|x| Some(Button::GridButton { x, y }))
+                .collect(),
+        );
+    }
+    rows
+}
+
+/// The color of every pad, laid out as in `pad_layout`
+fn render_pads(buttons: &Buttons) -> Vec<Vec<Option<RgbColor>>> {
+    let color = |button: &Button| match buttons.get(button) {
+        None => RgbColor { r: 0, g: 0, b: 0 },
+        Some(ButtonStyle::Rgb(color)) => *color,
+        // Animated styles are shown in their first color
+        Some(
+            ButtonStyle::Palette(color) | ButtonStyle::Flash(color, _) | ButtonStyle::Pulse(color),
+        ) => palette_to_rgb(color.0),
+    };
+
+    pad_layout()
+        .iter()
+        .map(|row| row.iter().map(|pad| pad.as_ref().map(color)).collect())
+        .collect()
+}
+
+fn write_png(pads: &[Vec<Option<RgbColor>>], path: &Path) -> anyhow::Result<()> {
+    let height = pads.len() as u32 * PAD_SIZE + PAD_GAP;
+    let width = pads[0].len() as u32 * PAD_SIZE + PAD_GAP;
+
+    let mut data = Vec::with_capacity((width * height * 3) as usize);
+    for py in 0..height {
+        for px in 0..width {
+            let (row, y_in_pad) = (py / PAD_SIZE, py % PAD_SIZE);
+            let (column, x_in_pad) = (px / PAD_SIZE, px % PAD_SIZE);
+            let color = pads
+                .get(row as usize)
+                .and_then(|row| row.get(column as usize).copied().flatten())
+                .filter(|_| y_in_pad >= PAD_GAP && x_in_pad >= PAD_GAP)
+                .unwrap_or(BACKGROUND);
+            data.extend([color.r, color.g, color.b]);
+        }
+    }
+
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&data)?;
+    Ok(())
+}
+
+fn to_ansi(pads: &[Vec<Option<RgbColor>>]) -> String {
+    let mut out = String::new();
+    for row in pads {
+        for pad in row {
+            match pad {
+                Some(c) => out += &format!("\x1b[48;2;{};{};{}m  \x1b[0m", c.r, c.g, c.b),
+                None => out += "  ",
+            }
+        }
+        out += "\n";
+    }
+    out
+}
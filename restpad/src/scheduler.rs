@@ -0,0 +1,61 @@
+use tokio::time::{interval_at, Instant, Interval, MissedTickBehavior};
+
+use crate::{
+    payload::{ImageSource, Payload},
+    render::FRAME_DURATION,
+};
+
+/// Wakes up the app for every animation frame, as long as the page has
+/// something that moves
+///
+/// Frames are counted from the time the page loaded rather than from the number
+/// of wakeups, so animations keep their pace when the app falls behind.
+#[derive(Default)]
+pub struct FrameScheduler {
+    running: Option<Running>,
+}
+
+struct Running {
+    started: Instant,
+    interval: Interval,
+}
+
+impl FrameScheduler {
+    /// Start over at frame 0 if the page has anything that moves, and stop
+    /// otherwise
+    pub fn start(&mut self, page: Option<&Payload>) {
+        self.running = page.filter(|page| is_animated(page)).map(|_| {
+            let started = Instant::now();
+            let mut interval = interval_at(started + FRAME_DURATION, FRAME_DURATION);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            Running { started, interval }
+        });
+    }
+
+    /// The frame that should be shown right now
+    pub fn frame(&self) -> i32 {
+        self.running.as_ref().map_or(0, |running| {
+            (running.started.elapsed().as_millis() / FRAME_DURATION.as_millis()) as i32
+        })
+    }
+
+    /// Wait until the next frame is due
+    ///
+    /// Returns `None` right away if nothing is running.
+    pub async fn next_frame(&mut self) -> Option<i32> {
+        self.running.as_mut()?.interval.tick().await;
+        Some(self.frame())
+    }
+}
+
+/// Whether a page has texts to scroll, clocks to update, images or buttons to
+/// animate
+fn is_animated(page: &Payload) -> bool {
+    let animated_images = page
+        .images
+        .iter()
+        .any(|image| matches!(&image.source, ImageSource::Frames { frames } if frames.len() > 1));
+    let animated_buttons = page.buttons.iter().any(|button| button.animation.is_some());
+    let has_texts = !page.text.is_empty() || !page.clocks.is_empty() || !page.countdowns.is_empty();
+    has_texts || animated_images || animated_buttons
+}
@@ -0,0 +1,41 @@
+use anyhow::{bail, Context};
+use http_body_util::{BodyExt, Empty};
+use hyper::{body::Bytes, Response};
+use hyper_util::client::legacy::Client;
+use hyperlocal::{UnixClientExt, UnixConnector};
+use percent_encoding::percent_decode_str;
+use reqwest::{header::HeaderMap, Url};
+
+/// The URL scheme for HTTP over a Unix domain socket
+///
+/// The host part is the percent-encoded path of the socket, for example
+/// `http+unix://%2Frun%2Fpad.sock/page` requests `/page` from `/run/pad.sock`.
+pub static SCHEME: &str = "http+unix";
+
+/// Do a GET request over a Unix domain socket
+pub async fn get(url: &Url, headers: &HeaderMap) -> anyhow::Result<Response<Bytes>> {
+    let Some(host) = url.host_str() else {
+        bail!("Missing socket path: {}", url);
+    };
+    let socket = percent_decode_str(host)
+        .decode_utf8()
+        .with_context(|| format!("Invalid socket path: {}", url))?;
+
+    let path_and_query = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+
+    let mut request = hyper::Request::get(hyperlocal::Uri::new(socket.as_ref(), &path_and_query))
+        .body(Empty::<Bytes>::new())?;
+    request.headers_mut().extend(headers.clone());
+
+    let client: Client<UnixConnector, Empty<Bytes>> = Client::unix();
+    let response = client
+        .request(request)
+        .await
+        .with_context(|| format!("Failed to connect to {}", socket))?;
+    let (parts, body) = response.into_parts();
+    let body = body.collect().await?.to_bytes();
+    Ok(Response::from_parts(parts, body))
+}
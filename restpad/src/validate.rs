@@ -0,0 +1,377 @@
+use std::collections::{BTreeSet, HashMap};
+
+use anyhow::bail;
+use chrono::Utc;
+
+use crate::{
+    clock::{is_valid_format, timezone_offset, widest_clock_text, widest_countdown_text},
+    cookies::CookieJar,
+    credentials::Credentials,
+    navigator::Navigator,
+    page_format::ParseError,
+    payload::{Action, ImageSource, Payload, Target},
+    render::{button_size, chart_size, progress_size, text_size},
+    GRID_WIDTH,
+};
+
+/// Load a page and report everything that is wrong with it
+///
+/// Fails if the page can't be loaded, or if any problems were found.
+pub async fn run(url: &str, credentials: Credentials) -> anyhow::Result<()> {
+    let mut navigator = Navigator::new(credentials, CookieJar::load()?)?;
+    navigator.open(url)?;
+    if let Err(e) = navigator.finish_load().await {
+        if let Some(ParseError {
+            message,
+            location: Some((line, column)),
+        }) = e.downcast_ref()
+        {
+            bail!("{}:{}:{}: {}", url, line, column, message);
+        }
+        return Err(e);
+    }
+
+    let Some(payload) = navigator.current() else {
+        bail!("{}: page did not load", url);
+    };
+    let problems = find_problems(&navigator, payload).await;
+    for problem in &problems {
+        println!("{}: {}", url, problem);
+    }
+    if !problems.is_empty() {
+        bail!("{} problem(s) found", problems.len());
+    }
+    println!("{}: OK", url);
+    Ok(())
+}
+
+/// Check a page and the inline pages it contains for layout mistakes and
+/// links to pages that can't be loaded
+async fn find_problems(navigator: &Navigator, payload: &Payload) -> Vec<String> {
+    let mut problems = vec![];
+    // Images are loaded along with the page, so any that are left failed to load
+    for (i, image) in payload.images.iter().enumerate() {
+        if let ImageSource::Src { src } = &image.source {
+            problems.push(format!(
+                "Image {} at ({}, {}) can't be loaded from {}",
+                i, image.x, image.y, src
+            ));
+        }
+    }
+
+    let mut pages = vec![(String::new(), payload)];
+
+    while let Some((prefix, page)) = pages.pop() {
+        problems.extend(
+            check_layout(page)
+                .into_iter()
+                .map(|problem| format!("{}{}", prefix, problem)),
+        );
+
+        for (i, button) in page.buttons.iter().enumerate() {
+            match &button.on_press {
+                Some(Action::Navigate(Target::Href { href })) => {
+                    if let Err(e) = navigator.check_link(href).await {
+                        problems.push(format!(
+                            "{}Button {} navigates to {}, which can't be loaded: {:#}",
+                            prefix, i, href, e
+                        ));
+                    }
+                }
+                Some(Action::Navigate(Target::Page { page })) => {
+                    pages.push((format!("{}Inline page of button {}: ", prefix, i), page));
+                }
+                _ => {}
+            }
+        }
+    }
+    problems
+}
+
+/// Check the elements of a page for things that won't show up the way
+/// the author probably intended
+fn check_layout(payload: &Payload) -> Vec<String> {
+    let mut problems = vec![];
+
+    // Which button is on each pad
+    let mut occupied: HashMap<(u32, u32), usize> = HashMap::new();
+
+    for (i, button) in payload.buttons.iter().enumerate() {
+        let name = format!("Button {} at ({}, {})", i, button.x, button.y);
+        if !check_on_grid(&mut problems, &name, button.x) {
+            continue;
+        }
+
+        if let Some(animation) = &button.animation {
+            if animation.keyframes.is_empty() {
+                problems.push(format!("{} has an animation without keyframes", name));
+            } else if animation.keyframes.iter().all(|k| k.duration_ms == 0) {
+                problems.push(format!(
+                    "{} has an animation whose keyframes all last 0 ms",
+                    name
+                ));
+            }
+        }
+
+        let (width, height) = button_size(button);
+        let right = button.x + width;
+        if right > GRID_WIDTH + 1 {
+            problems.push(format!("{} runs past the right edge of the grid", name));
+        } else if right > GRID_WIDTH {
+            problems.push(format!("{} runs into the scroll column", name));
+        }
+
+        let overlaps = (button.x..right.min(GRID_WIDTH))
+            .flat_map(|x| (button.y..button.y.saturating_add(height)).map(move |y| (x, y)))
+            .filter_map(|pad| occupied.insert(pad, i))
+            .collect::<BTreeSet<_>>();
+        for other in overlaps {
+            problems.push(format!("{} overlaps button {}", name, other));
+        }
+    }
+
+    // Clocks and countdowns are laid out like the widest texts they can show
+    let mut texts = vec![];
+    texts.extend(
+        payload
+            .text
+            .iter()
+            .enumerate()
+            .map(|(i, text)| (format!("Text {}", i), text.clone())),
+    );
+    texts.extend(
+        payload
+            .clocks
+            .iter()
+            .enumerate()
+            .map(|(i, clock)| (format!("Clock {}", i), widest_clock_text(clock))),
+    );
+    texts.extend(payload.countdowns.iter().enumerate().map(|(i, countdown)| {
+        let text = widest_countdown_text(countdown, &payload.glyphs);
+        (format!("Countdown {}", i), text)
+    }));
+    for (kind, text) in texts {
+        let name = format!("{} at ({}, {})", kind, text.x, text.y);
+        if !check_on_grid(&mut problems, &name, text.x) {
+            continue;
+        }
+
+        if let Some(width) = text.width {
+            check_right_edge(&mut problems, &name, text.x, width);
+        }
+        let size = text_size(&text);
+        check_overlaps(&mut problems, &occupied, &name, (text.x, text.y), size);
+    }
+
+    for (i, clock) in payload.clocks.iter().enumerate() {
+        let name = format!("Clock {} at ({}, {})", i, clock.x, clock.y);
+        if timezone_offset(&clock.timezone, Utc::now()).is_none() {
+            problems.push(format!(
+                "{} has an unknown timezone {:?}; use local, UTC, a name like \
+                 Europe/Amsterdam or an offset like +02:00",
+                name, clock.timezone
+            ));
+        }
+        if !is_valid_format(&clock.format) {
+            problems.push(format!("{} has an invalid format {:?}", name, clock.format));
+        }
+    }
+
+    for (i, image) in payload.images.iter().enumerate() {
+        let name = format!("Image {} at ({}, {})", i, image.x, image.y);
+        if !check_on_grid(&mut problems, &name, image.x) {
+            continue;
+        }
+        if let Some(width) = image.width {
+            check_right_edge(&mut problems, &name, image.x, width);
+        }
+    }
+
+    for (i, progress) in payload.progress.iter().enumerate() {
+        let name = format!("Progress bar {} at ({}, {})", i, progress.x, progress.y);
+        if !check_on_grid(&mut problems, &name, progress.x) {
+            continue;
+        }
+
+        let size = progress_size(progress);
+        check_right_edge(&mut problems, &name, progress.x, size.0);
+        if progress.max <= 0.0 {
+            problems.push(format!(
+                "{} has a max of {}, so it never fills",
+                name, progress.max
+            ));
+        }
+        if progress.colors.is_empty() {
+            problems.push(format!("{} has no colors", name));
+        }
+
+        let position = (progress.x, progress.y);
+        check_overlaps(&mut problems, &occupied, &name, position, size);
+    }
+
+    for (i, chart) in payload.charts.iter().enumerate() {
+        let name = format!("Chart {} at ({}, {})", i, chart.x, chart.y);
+        if !check_on_grid(&mut problems, &name, chart.x) {
+            continue;
+        }
+
+        let size = chart_size(chart);
+        check_right_edge(&mut problems, &name, chart.x, size.0);
+        if let (Some(min), Some(max)) = (chart.min, chart.max) {
+            if min >= max {
+                problems.push(format!(
+                    "{} has a min of {} and a max of {}",
+                    name, min, max
+                ));
+            }
+        }
+
+        check_overlaps(&mut problems, &occupied, &name, (chart.x, chart.y), size);
+    }
+
+    problems
+}
+
+/// Report an element that starts to the right of the grid, and return whether
+/// it is on the grid at all
+fn check_on_grid(problems: &mut Vec<String>, name: &str, x: u32) -> bool {
+    if x >= GRID_WIDTH {
+        problems.push(format!(
+            "{} is outside the grid, which has columns 0 to {}",
+            name,
+            GRID_WIDTH - 1
+        ));
+    }
+    x < GRID_WIDTH
+}
+
+/// Report an element that is too wide for the grid
+fn check_right_edge(problems: &mut Vec<String>, name: &str, x: u32, width: u32) {
+    if x.saturating_add(width) > GRID_WIDTH {
+        problems.push(format!("{} runs past the right edge of the grid", name));
+    }
+}
+
+/// Report the buttons on any pad of an element
+///
+/// Goes through the buttons rather than the pads, as a text, bar or chart can
+/// be much bigger than the grid.
+fn check_overlaps(
+    problems: &mut Vec<String>,
+    occupied: &HashMap<(u32, u32), usize>,
+    name: &str,
+    (x, y): (u32, u32),
+    (width, height): (u32, u32),
+) {
+    let columns = x..x.saturating_add(width);
+    let rows = y..y.saturating_add(height);
+    let overlaps = occupied
+        .iter()
+        .filter(|((x, y), _)| columns.contains(x) && rows.contains(y))
+        .map(|(_, &button)| button)
+        .collect::<BTreeSet<_>>();
+    for button in overlaps {
+        problems.push(format!("{} overlaps button {}", name, button));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_problems() {
+        let cases: &[(&str, &[&str])] = &[
+            (r#"{}"#, &[]),
+            (
+                r##"{"buttons": [
+                    {"x": 8, "y": 0, "color": "#FFFFFF"},
+                    {"x": 7, "y": 1, "width": 2, "color": "#FFFFFF"},
+                    {"x": 6, "y": 2, "width": 4, "color": "#FFFFFF"}
+                ]}"##,
+                &[
+                    "Button 0 at (8, 0) is outside the grid, which has columns 0 to 7",
+                    "Button 1 at (7, 1) runs into the scroll column",
+                    "Button 2 at (6, 2) runs past the right edge of the grid",
+                ],
+            ),
+            (
+                r##"{"buttons": [
+                    {"x": 0, "y": 0, "width": 2, "height": 2, "color": "#FFFFFF"},
+                    {"x": 1, "y": 1, "color": "#FFFFFF"},
+                    {"x": 2, "y": 0, "color": "#FFFFFF", "animation": {"keyframes": []}}
+                ]}"##,
+                &[
+                    "Button 1 at (1, 1) overlaps button 0",
+                    "Button 2 at (2, 0) has an animation without keyframes",
+                ],
+            ),
+            (
+                r##"{"buttons": [{"x": 3, "y": 10, "color": "#FFFFFF"}],
+                    "text": [
+                        {"x": 0, "y": 8, "text": "Hi", "color": "#FFFFFF"},
+                        {"x": 4, "y": 0, "width": 5, "text": "Hi", "color": "#FFFFFF"},
+                        {"x": 9, "y": 0, "text": "Hi", "color": "#FFFFFF"}
+                    ]}"##,
+                &[
+                    "Text 0 at (0, 8) overlaps button 0",
+                    "Text 1 at (4, 0) runs past the right edge of the grid",
+                    "Text 2 at (9, 0) is outside the grid, which has columns 0 to 7",
+                ],
+            ),
+            (
+                r##"{"clocks": [
+                    {"x": 0, "y": 0, "timezone": "Mars", "format": "%Q", "color": "#FFFFFF"}
+                ]}"##,
+                &[
+                    "Clock 0 at (0, 0) has an unknown timezone \"Mars\"; use local, UTC, \
+                     a name like Europe/Amsterdam or an offset like +02:00",
+                    "Clock 0 at (0, 0) has an invalid format \"%Q\"",
+                ],
+            ),
+            (
+                r##"{"images": [
+                    {"x": 8, "y": 0, "pixels": [["#FFFFFF"]]},
+                    {"x": 6, "y": 0, "width": 3, "pixels": [["#FFFFFF"]]}
+                ]}"##,
+                &[
+                    "Image 0 at (8, 0) is outside the grid, which has columns 0 to 7",
+                    "Image 1 at (6, 0) runs past the right edge of the grid",
+                ],
+            ),
+            (
+                r##"{"buttons": [{"x": 5, "y": 3, "color": "#FFFFFF"}],
+                    "progress": [
+                        {"x": 4, "y": 3, "length": 5, "value": 1, "max": 0, "colors": []},
+                        {"x": 5, "y": 0, "length": 3, "orientation": "vertical",
+                         "value": 1, "colors": ["#FFFFFF"]}
+                    ]}"##,
+                &[
+                    "Progress bar 0 at (4, 3) runs past the right edge of the grid",
+                    "Progress bar 0 at (4, 3) has a max of 0, so it never fills",
+                    "Progress bar 0 at (4, 3) has no colors",
+                    "Progress bar 0 at (4, 3) overlaps button 0",
+                ],
+            ),
+            (
+                r##"{"buttons": [{"x": 1, "y": 1, "color": "#FFFFFF"}],
+                    "charts": [
+                        {"x": 0, "y": 0, "height": 2, "values": [1, 2], "min": 5, "max": 5,
+                         "color": "#FFFFFF"},
+                        {"x": 6, "y": 4, "width": 3, "height": 1, "values": [1],
+                         "color": "#FFFFFF"}
+                    ]}"##,
+                &[
+                    "Chart 0 at (0, 0) has a min of 5 and a max of 5",
+                    "Chart 0 at (0, 0) overlaps button 0",
+                    "Chart 1 at (6, 4) runs past the right edge of the grid",
+                ],
+            ),
+        ];
+
+        for (page, expected) in cases {
+            let payload: Payload = serde_json::from_str(page).unwrap();
+            assert_eq!(check_layout(&payload), *expected, "{}", page);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Golden-file tests for rendering
+//!
+//! Every page in `tests/fixtures` is rendered at the first `FRAMES` animation
+//! frames and at every scroll position, and compared against the file with the
+//! same name in `tests/golden`. Frames that look the same as the one before are
+//! left out, so the golden files show exactly when text scrolling moves.
+//!
+//! After an intended change in rendering, update the golden files with:
+//!
+//! ```sh
+//! UPDATE_GOLDEN=1 cargo test
+//! ```
+//!
+//! and review the diff.
+
+use std::{collections::HashSet, env, fs, path::Path};
+
+use chrono::{DateTime, Utc};
+use restpad::{
+    any_launchpad::{ButtonStyle, Buttons},
+    page_format::PageFormat,
+    payload::Payload,
+    render::{pad_layout, PadState, FRAME_DURATION},
+};
+
+/// How many animation frames to render of every page
+static FRAMES: i32 = 60;
+
+/// When the first frame is rendered, as a Unix timestamp, so clocks and
+/// countdowns always show the same: 2024-05-01 09:29:57 UTC
+static START_TIMESTAMP: i64 = 1_714_555_797;
+
+#[test]
+fn golden_files() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let update = env::var_os("UPDATE_GOLDEN").is_some();
+
+    let mut fixtures = fs::read_dir(root.join("fixtures"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    fixtures.sort();
+    assert!(!fixtures.is_empty(), "No fixtures found");
+
+    let mut mismatches = vec![];
+    for fixture in fixtures {
+        let name = fixture.file_stem().unwrap().to_string_lossy().to_string();
+        let contents = fs::read(&fixture).unwrap();
+        let payload = PageFormat::detect(None, &fixture.to_string_lossy())
+            .parse(&contents)
+            .unwrap_or_else(|e| panic!("{}: {}", fixture.display(), e));
+
+        let actual = render_all(&payload);
+        let golden = root.join("golden").join(format!("{}.txt", name));
+        if update {
+            fs::write(&golden, &actual).unwrap();
+        } else if fs::read_to_string(&golden).ok().as_deref() != Some(actual.as_str()) {
+            mismatches.push(name);
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "Rendering differs from the golden files for: {}\n\
+         Run `UPDATE_GOLDEN=1 cargo test` and review the diff if this is intended.",
+        mismatches.join(", ")
+    );
+}
+
+/// Render a page at every distinct frame, and at every scroll position
+fn render_all(payload: &Payload) -> String {
+    let pressed_buttons = HashSet::new();
+    let mut state = PadState {
+        page: Some(payload),
+        pressed_buttons: &pressed_buttons,
+        y_scroll: 0,
+        frame: 0,
+        now: time_at(0),
+        brightness: 8,
+        has_history: false,
+        has_future: false,
+        is_loading: false,
+        load_failed: false,
+        is_bookmarked: false,
+    };
+
+    let mut out = String::new();
+    let mut previous = None;
+    for frame in 0..FRAMES {
+        state.frame = frame;
+        state.now = time_at(frame);
+        let grid = format_buttons(&state.calculate_buttons());
+        if previous.as_ref() != Some(&grid) {
+            out += &format!("# frame {}\n{}\n", frame, grid);
+            previous = Some(grid);
+        }
+    }
+
+    state.frame = 0;
+    state.now = time_at(0);
+    for y_scroll in 1..=state.scrollable_y_height() {
+        state.y_scroll = y_scroll;
+        let grid = format_buttons(&state.calculate_buttons());
+        out += &format!("# scroll {}\n{}\n", y_scroll, grid);
+    }
+    out
+}
+
+/// The time at which the given frame is rendered
+fn time_at(frame: i32) -> DateTime<Utc> {
+    DateTime::from_timestamp(START_TIMESTAMP, 0).unwrap() + FRAME_DURATION * frame as u32
+}
+
+/// Write out the style of every pad, one row of pads per line
+///
+/// RGB colors are written as hex, palette colors as `p` and their index, pulsing
+/// pads as `~` and the index, and flashing pads as both indices. Dark pads are
+/// shown as `.`.
+fn format_buttons(buttons: &Buttons) -> String {
+    let mut out = String::new();
+    for row in pad_layout() {
+        let line = row
+            .iter()
+            .map(|pad| {
+                let cell = match pad.and_then(|pad| buttons.get(&pad)) {
+                    _ if pad.is_none() => String::new(),
+                    None => ".".to_string(),
+                    Some(ButtonStyle::Rgb(c)) => format!("{:02x}{:02x}{:02x}", c.r, c.g, c.b),
+                    Some(ButtonStyle::Palette(c)) => format!("p{:03}", c.0),
+                    Some(ButtonStyle::Pulse(c)) => format!("~{:03}", c.0),
+                    Some(ButtonStyle::Flash(a, b)) => format!("{:03}/{:03}", a.0, b.0),
+                };
+                format!("{:8}", cell)
+            })
+            .collect::<String>();
+        out += line.trim_end();
+        out += "\n";
+    }
+    out
+}